@@ -0,0 +1,206 @@
+use core::task::Waker;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::fmt;
+use super::{Wakers, WakersRef, PendOutcome, Register};
+
+const WAITING: usize = 0;
+const REGISTERING: usize = 0b01;
+const WAKING: usize = 0b10;
+
+/// Lock-free storage for a single waker, usable from interrupt handlers and other contexts that
+/// can't block.
+///
+/// Follows the same protocol as `futures`' `AtomicWaker`: the slot is only touched by whoever moved
+/// the state out of `WAITING`, and a wake that arrives mid-registration is handed off to the
+/// registering thread rather than being lost.
+pub struct AtomicWakerSlot {
+    state: AtomicUsize,
+    /// # Safety
+    ///
+    /// Only accessed while holding either the `REGISTERING` or `WAKING` bit, after observing `WAITING`.
+    waker: UnsafeCell<Option<Waker>>,
+    /// The [`Register`] key of the stored waker, bumped whenever a new one takes its place.
+    ///
+    /// # Safety
+    ///
+    /// Only accessed while holding the `REGISTERING` bit.
+    key: UnsafeCell<usize>,
+}
+
+unsafe impl Send for AtomicWakerSlot { }
+unsafe impl Sync for AtomicWakerSlot { }
+
+impl AtomicWakerSlot {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(WAITING),
+            waker: UnsafeCell::new(None),
+            key: UnsafeCell::new(0),
+        }
+    }
+
+    /// Runs `f` on the slot and its key, unless another thread is in the middle of using it.
+    ///
+    /// Any wake that arrives while `f` runs is delivered to whatever `f` leaves in the slot.
+    fn update<R, F: FnOnce(&mut Option<Waker>, &mut usize) -> R>(&self, f: F) -> Option<R> {
+        self.state.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire).ok()?;
+        let res = unsafe { f(&mut *self.waker.get(), &mut *self.key.get()) };
+
+        if self.state.compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            // a wake arrived while we held the slot, so it's our job to deliver it
+            let woken = unsafe { (*self.waker.get()).take() };
+            self.state.swap(WAITING, Ordering::AcqRel);
+            if let Some(w) = woken {
+                w.wake()
+            }
+        }
+
+        Some(res)
+    }
+
+    /// Stores `waker` under a new key, handing back whatever it replaced.
+    fn replace(slot: &mut Option<Waker>, key: &mut usize, waker: &Waker) -> Option<Waker> {
+        *key = key.wrapping_add(1);
+        slot.replace(waker.clone())
+    }
+
+    /// Removes the stored waker, unless another thread is in the middle of using the slot.
+    pub fn take(&self) -> Option<Waker> {
+        match self.state.fetch_or(WAKING, Ordering::AcqRel) {
+            WAITING => {
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, Ordering::Release);
+                waker
+            },
+            // a registration in progress will notice the WAKING bit and wake its waker itself,
+            // otherwise someone else is already waking the slot
+            _ => None,
+        }
+    }
+}
+
+impl Wakers for AtomicWakerSlot {
+    /// A slot that is concurrently being woken or registered reports [`PendOutcome::Full`].
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+        let res = self.update(|slot, key| match slot {
+            Some(w) if w.will_wake(waker) => (PendOutcome::AlreadyRegistered, None),
+            Some(_) => (PendOutcome::Evicted, Self::replace(slot, key, waker)),
+            None => (PendOutcome::Inserted, Self::replace(slot, key, waker)),
+        });
+
+        match res {
+            Some((outcome, evicted)) => {
+                if let Some(w) = evicted {
+                    w.wake()
+                }
+                outcome
+            },
+            None => PendOutcome::Full,
+        }
+    }
+
+    /// A slot that is concurrently being woken or registered reports that it found nothing.
+    fn remove_by_ref(&self, waker: &Waker) -> bool {
+        let removed = self.update(|slot, _| match slot {
+            Some(w) if w.will_wake(waker) => slot.take(),
+            _ => None,
+        });
+        removed.flatten().is_some()
+    }
+}
+
+/// The slot only holds one registration, so a new one evicts (and wakes) whichever came before it.
+impl Register for AtomicWakerSlot {
+    type Key = usize;
+
+    /// A slot that is concurrently being woken or registered fails as if there were no room.
+    fn register_key_by_ref(&self, key: Option<usize>, waker: &Waker) -> Option<usize> {
+        let (key, evicted) = self.update(|slot, current| match (slot.as_mut(), key) {
+            (Some(w), Some(key)) if key == *current => {
+                if !w.will_wake(waker) {
+                    *w = waker.clone();
+                }
+                (key, None)
+            },
+            _ => {
+                let evicted = Self::replace(slot, current, waker);
+                (*current, evicted)
+            },
+        })?;
+
+        if let Some(w) = evicted {
+            w.wake()
+        }
+        Some(key)
+    }
+
+    /// A slot that is concurrently being woken or registered reports that the registration is gone,
+    /// as if it had been woken.
+    fn deregister_key_by_ref(&self, key: usize) -> bool {
+        let removed = self.update(|slot, current| match slot {
+            Some(_) if key == *current => slot.take(),
+            _ => None,
+        });
+        removed.flatten().is_some()
+    }
+}
+
+impl WakersRef for AtomicWakerSlot {
+    #[inline]
+    fn wake_by_ref(&self) {
+        if let Some(w) = self.take() {
+            w.wake()
+        }
+    }
+
+    /// A registration racing with this may still see it as a regular wake, and clear itself.
+    fn wake_and_retain_by_ref(&self) {
+        let waker = match self.state.fetch_or(WAKING, Ordering::AcqRel) {
+            WAITING => {
+                let waker = unsafe { (*self.waker.get()).clone() };
+                self.state.fetch_and(!WAKING, Ordering::Release);
+                waker
+            },
+            _ => None,
+        };
+        if let Some(w) = waker {
+            w.wake()
+        }
+    }
+
+    #[inline]
+    fn wake_n_by_ref(&self, n: usize) -> usize {
+        match n {
+            0 => 0,
+            _ => match self.take() {
+                Some(w) => {
+                    w.wake();
+                    1
+                },
+                None => 0,
+            },
+        }
+    }
+}
+
+impl Default for AtomicWakerSlot {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for AtomicWakerSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AtomicWakerSlot")
+            .field("state", &self.state.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl const_default::ConstDefault for AtomicWakerSlot {
+    const DEFAULT: Self = Self::new();
+}
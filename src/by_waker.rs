@@ -0,0 +1,94 @@
+use core::task::Waker;
+use super::{Wakers, WakersRef, PendOutcome, Register};
+
+/// Lets storage that only implements [`Wakers`] stand in where [`Register`] is needed, as by
+/// [`Notify`](crate::Notify), keying each registration by its waker.
+///
+/// Such storage deduplicates wakers, so every registration made by the same task shares one entry.
+/// Withdrawing a registration whose entry was still stored therefore wakes that task, letting any of
+/// its other registrations put the entry back when polled. That costs a spurious wakeup, where
+/// leaving it out could lose one.
+#[derive(Debug, Clone, Default)]
+pub struct ByWaker<W> {
+    wakers: W,
+}
+
+impl<W> ByWaker<W> {
+    #[inline]
+    pub const fn new(wakers: W) -> Self {
+        Self { wakers }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.wakers
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.wakers
+    }
+}
+
+impl<W: WakersRef> WakersRef for ByWaker<W> {
+    #[inline]
+    fn wake_by_ref(&self) {
+        self.wakers.wake_by_ref()
+    }
+
+    #[inline]
+    fn wake_n_by_ref(&self, n: usize) -> usize {
+        self.wakers.wake_n_by_ref(n)
+    }
+
+    #[inline]
+    fn wake_and_retain_by_ref(&self) {
+        self.wakers.wake_and_retain_by_ref()
+    }
+}
+
+impl<W: Wakers> Wakers for ByWaker<W> {
+    #[inline]
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+        self.wakers.try_pend_by_ref(waker)
+    }
+
+    #[inline]
+    fn remove_by_ref(&self, waker: &Waker) -> bool {
+        self.wakers.remove_by_ref(waker)
+    }
+}
+
+impl<W: Wakers> Register for ByWaker<W> {
+    type Key = Waker;
+
+    fn register_key_by_ref(&self, key: Option<Waker>, waker: &Waker) -> Option<Waker> {
+        let key = match key {
+            Some(key) if key.will_wake(waker) => key,
+            stale => {
+                if let Some(stale) = stale {
+                    self.deregister_key_by_ref(stale);
+                }
+                waker.clone()
+            },
+        };
+
+        match self.wakers.try_pend_by_ref(&key) {
+            PendOutcome::Full => None,
+            _ => Some(key),
+        }
+    }
+
+    fn deregister_key_by_ref(&self, key: Waker) -> bool {
+        let removed = self.wakers.remove_by_ref(&key);
+        if removed {
+            key.wake()
+        }
+        removed
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for ByWaker<W> {
+    const DEFAULT: Self = Self::new(W::DEFAULT);
+}
@@ -0,0 +1,47 @@
+use core::cell::RefCell;
+use critical_section::Mutex;
+use super::{SyncWakers, Lock};
+
+/// A [`Lock`] that takes a critical section rather than blocking.
+pub struct CsLock<T>(Mutex<RefCell<T>>);
+
+impl<T> CsLock<T> {
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self(Mutex::new(RefCell::new(value)))
+    }
+}
+
+impl<T> Lock<T> for CsLock<T> {
+    #[inline]
+    fn new(value: T) -> Self {
+        Self::new(value)
+    }
+
+    #[inline]
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        critical_section::with(|cs| f(&mut self.0.borrow_ref_mut(cs)))
+    }
+
+    #[inline]
+    fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().get_mut()
+    }
+
+    #[inline]
+    fn into_inner(self) -> T {
+        self.0.into_inner().into_inner()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<T: const_default::ConstDefault> const_default::ConstDefault for CsLock<T> {
+    const DEFAULT: Self = Self::new(T::DEFAULT);
+}
+
+/// [`SyncWakers`] guarded by a critical section rather than a lock, making it suitable for statics
+/// shared with interrupt handlers.
+///
+/// [`from_lock`](SyncWakers::from_lock) is `const`, so it can build one in a static initializer:
+/// `CsWakers::from_lock(CsLock::new(wakers))`.
+pub type CsWakers<W> = SyncWakers<W, CsLock<W>>;
@@ -0,0 +1,257 @@
+use core::future::Future;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use core::pin::Pin;
+use core::{mem, fmt};
+use super::permits::Permits;
+use super::{Register, Registration};
+
+/// An event that, once [`set`](ManualResetEvent::set), releases every waiter until it's
+/// [`reset`](ManualResetEvent::reset).
+///
+/// Built on any [`Register`] container, or any other [`Wakers`](crate::Wakers) storage wrapped in
+/// [`ByWaker`](crate::ByWaker). Each [`ManualResetWait`] holds its own keyed [`Registration`], so one
+/// that is dropped early withdraws exactly its own registration, even when the same task is waiting
+/// on the event more than once. Through `ByWaker` those waits share one entry instead, so
+/// withdrawing it wakes the task to have the other put it back.
+pub struct ManualResetEvent<W> {
+    wakers: W,
+    set: AtomicBool,
+}
+
+impl<W> ManualResetEvent<W> {
+    #[inline]
+    pub const fn new(wakers: W) -> Self {
+        Self {
+            wakers,
+            set: AtomicBool::new(false),
+        }
+    }
+
+    #[inline]
+    pub fn is_set(&self) -> bool {
+        self.set.load(Ordering::Acquire)
+    }
+
+    /// Stops releasing waiters. Those that were already woken by [`set`](ManualResetEvent::set)
+    /// but haven't been polled since will wait again.
+    #[inline]
+    pub fn reset(&self) {
+        self.set.store(false, Ordering::Release)
+    }
+}
+
+impl<W: Register> ManualResetEvent<W> {
+    /// Sets the event, waking every task waiting on it.
+    pub fn set(&self) {
+        self.set.store(true, Ordering::Release);
+        self.wakers.wake_by_ref();
+    }
+
+    /// Returns a future that completes once the event is set, or immediately if it already is.
+    #[inline]
+    pub fn wait(&self) -> ManualResetWait<'_, W> {
+        ManualResetWait {
+            event: self,
+            registration: Registration::new(&self.wakers),
+            done: false,
+        }
+    }
+}
+
+impl<W: Default> Default for ManualResetEvent<W> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(W::default())
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for ManualResetEvent<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ManualResetEvent")
+            .field("wakers", &self.wakers)
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for ManualResetEvent<W> {
+    const DEFAULT: Self = Self::new(W::DEFAULT);
+}
+
+/// The future returned by [`ManualResetEvent::wait`].
+pub struct ManualResetWait<'a, W: Register> {
+    event: &'a ManualResetEvent<W>,
+    registration: Registration<'a, W>,
+    done: bool,
+}
+
+impl<W: Register> Unpin for ManualResetWait<'_, W> { }
+
+impl<W: Register> Future for ManualResetWait<'_, W> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(())
+        }
+
+        if !this.event.is_set() {
+            this.registration.pend(cx.waker());
+
+            // the event may have been set before the registration landed
+            if !this.event.is_set() {
+                return Poll::Pending
+            }
+        }
+
+        this.registration.deregister();
+        this.done = true;
+        Poll::Ready(())
+    }
+}
+
+impl<W: Register> fmt::Debug for ManualResetWait<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ManualResetWait")
+            .field("registration", &self.registration)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// An event that releases exactly one waiter each time it's [`set`](AutoResetEvent::set), resetting
+/// itself as that waiter completes.
+///
+/// Setting the event while nobody is waiting leaves it set for the next waiter. That doesn't
+/// accumulate: setting it twice with nobody waiting still only releases one. Releases handed to
+/// waiters that were woken but haven't been polled yet don't count towards that. Like
+/// [`ManualResetEvent`], this works with any [`Register`] container, or plain storage wrapped in
+/// [`ByWaker`](crate::ByWaker).
+pub struct AutoResetEvent<W> {
+    wakers: W,
+    permits: Permits,
+}
+
+impl<W> AutoResetEvent<W> {
+    #[inline]
+    pub const fn new(wakers: W) -> Self {
+        Self {
+            wakers,
+            permits: Permits::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_set(&self) -> bool {
+        self.permits.is_available()
+    }
+
+    /// Clears the event, along with any releases handed to waiters that haven't been polled since.
+    #[inline]
+    pub fn reset(&self) {
+        self.permits.clear()
+    }
+}
+
+impl<W: Register> AutoResetEvent<W> {
+    /// Sets the event, waking the longest-waiting task.
+    pub fn set(&self) {
+        self.permits.release(|| self.wakers.wake_one_by_ref())
+    }
+
+    /// Returns a future that completes once it takes the event's release, resetting it.
+    #[inline]
+    pub fn wait(&self) -> AutoResetWait<'_, W> {
+        AutoResetWait {
+            event: self,
+            registration: Registration::new(&self.wakers),
+            registered: false,
+            done: false,
+        }
+    }
+}
+
+impl<W: Default> Default for AutoResetEvent<W> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(W::default())
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for AutoResetEvent<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AutoResetEvent")
+            .field("wakers", &self.wakers)
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for AutoResetEvent<W> {
+    const DEFAULT: Self = Self::new(W::DEFAULT);
+}
+
+/// The future returned by [`AutoResetEvent::wait`].
+pub struct AutoResetWait<'a, W: Register> {
+    event: &'a AutoResetEvent<W>,
+    registration: Registration<'a, W>,
+    registered: bool,
+    done: bool,
+}
+
+impl<W: Register> Unpin for AutoResetWait<'_, W> { }
+
+impl<W: Register> AutoResetWait<'_, W> {
+    /// Withdraws our registration, passing the release along to another waiter if we were woken for
+    /// one that we never took.
+    fn release(&mut self) {
+        if mem::take(&mut self.registered) && !self.registration.deregister() && !self.done {
+            let wakers = &self.event.wakers;
+            self.event.permits.forward(|| wakers.wake_one_by_ref());
+        }
+    }
+}
+
+impl<W: Register> Future for AutoResetWait<'_, W> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(())
+        }
+
+        if !this.event.permits.take() {
+            this.registered = this.registration.pend(cx.waker());
+
+            // the event may have been set before the registration landed
+            if !this.event.permits.take() {
+                return Poll::Pending
+            }
+        }
+
+        this.done = true;
+        this.release();
+        Poll::Ready(())
+    }
+}
+
+impl<W: Register> Drop for AutoResetWait<'_, W> {
+    #[inline]
+    fn drop(&mut self) {
+        self.release()
+    }
+}
+
+impl<W: Register> fmt::Debug for AutoResetWait<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AutoResetWait")
+            .field("registration", &self.registration)
+            .field("done", &self.done)
+            .finish()
+    }
+}
@@ -0,0 +1,66 @@
+use core::task::{Waker, RawWaker, RawWakerVTable};
+use core::marker::PhantomData;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+use super::WakersRef;
+
+/// Creates a waker that wakes everything registered with `wakers`.
+///
+/// This lets a container stand in as the waker of a child future, broadcasting its wakeups to every
+/// task waiting on the container.
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub fn arc_waker<W: WakersRef + Send + Sync + 'static>(wakers: Arc<W>) -> Waker {
+    let data = Arc::into_raw(wakers) as *const ();
+    unsafe {
+        Waker::from_raw(RawWaker::new(data, &ArcVTable::<W>::VTABLE))
+    }
+}
+
+/// Like `arc_waker`, but for a container that lives forever, such as a `static`.
+pub fn static_waker<W: WakersRef + Sync>(wakers: &'static W) -> Waker {
+    let data = wakers as *const W as *const ();
+    unsafe {
+        Waker::from_raw(RawWaker::new(data, &StaticVTable::<W>::VTABLE))
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+struct ArcVTable<W>(PhantomData<W>);
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<W: WakersRef + Send + Sync + 'static> ArcVTable<W> {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(Self::clone, Self::wake, Self::wake_by_ref, Self::drop);
+
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        Arc::increment_strong_count(data as *const W);
+        RawWaker::new(data, &Self::VTABLE)
+    }
+
+    unsafe fn wake(data: *const ()) {
+        Arc::from_raw(data as *const W).wake_by_ref()
+    }
+
+    unsafe fn wake_by_ref(data: *const ()) {
+        (*(data as *const W)).wake_by_ref()
+    }
+
+    unsafe fn drop(data: *const ()) {
+        drop(Arc::from_raw(data as *const W))
+    }
+}
+
+struct StaticVTable<W>(PhantomData<W>);
+
+impl<W: WakersRef + Sync> StaticVTable<W> {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(Self::clone, Self::wake_by_ref, Self::wake_by_ref, Self::drop);
+
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        RawWaker::new(data, &Self::VTABLE)
+    }
+
+    unsafe fn wake_by_ref(data: *const ()) {
+        (*(data as *const W)).wake_by_ref()
+    }
+
+    unsafe fn drop(_: *const ()) { }
+}
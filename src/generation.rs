@@ -0,0 +1,211 @@
+use core::task::Waker;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::fmt;
+use super::{Wakers, WakersRef, WakersMut, PendOutcome, Register, RegisterMut};
+
+/// Wraps a container with an epoch counter that's bumped every time it wakes.
+///
+/// A task reads the [`epoch`](GenerationWakers::epoch) before checking whether it can make progress,
+/// then registers with [`pend_if_unchanged`](GenerationWakers::pend_if_unchanged). If a wake happened
+/// in between, the registration is refused and the task knows to poll again instead of sleeping
+/// through the wakeup it just missed.
+#[derive(Debug, Default)]
+pub struct GenerationWakers<W> {
+    wakers: W,
+    epoch: AtomicUsize,
+}
+
+/// Returned by [`GenerationWakers::pend_if_unchanged`] when a wake has happened since the caller
+/// observed the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochChanged {
+    pub current: usize,
+}
+
+impl fmt::Display for EpochChanged {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "wakers were woken since the epoch was observed (now {})", self.current)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EpochChanged { }
+
+impl<W> GenerationWakers<W> {
+    #[inline]
+    pub const fn new(wakers: W) -> Self {
+        Self {
+            wakers,
+            epoch: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn epoch(&self) -> usize {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.wakers
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.wakers
+    }
+
+    #[inline]
+    fn advance(&self) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn advance_mut(&mut self) {
+        let epoch = self.epoch.get_mut();
+        *epoch = epoch.wrapping_add(1);
+    }
+}
+
+impl<W: Wakers> GenerationWakers<W> {
+    /// Registers `waker`, unless the container has been woken since `epoch` was observed.
+    ///
+    /// A wake that races with the registration also reports [`EpochChanged`]. The waker may be left
+    /// registered in that case, which at worst costs a spurious wakeup later.
+    pub fn pend_if_unchanged(&self, epoch: usize, waker: &Waker) -> Result<PendOutcome, EpochChanged> {
+        let current = self.epoch();
+        if current != epoch {
+            return Err(EpochChanged { current })
+        }
+
+        let outcome = self.wakers.try_pend_by_ref(waker);
+
+        match self.epoch() {
+            current if current == epoch => Ok(outcome),
+            current => Err(EpochChanged { current }),
+        }
+    }
+}
+
+impl<W: Clone> Clone for GenerationWakers<W> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            wakers: self.wakers.clone(),
+            epoch: AtomicUsize::new(self.epoch()),
+        }
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for GenerationWakers<W> {
+    const DEFAULT: Self = Self::new(W::DEFAULT);
+}
+
+impl<W: WakersMut> WakersMut for GenerationWakers<W> {
+    type Batch = W::Batch;
+
+    #[inline]
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        self.wakers.try_pend_evicting(waker)
+    }
+
+    #[inline]
+    fn take_all(&mut self) -> Self::Batch {
+        self.advance_mut();
+        self.wakers.take_all()
+    }
+
+    #[inline]
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        self.advance_mut();
+        self.wakers.take_n(n)
+    }
+
+    #[inline]
+    fn clone_all(&self) -> Self::Batch {
+        self.wakers.clone_all()
+    }
+
+    /// Removing a waker isn't a wake, so this leaves the epoch alone.
+    #[inline]
+    fn remove(&mut self, waker: &Waker) -> bool {
+        self.wakers.remove(waker)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.wakers.len()
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        self.wakers.capacity()
+    }
+
+    #[inline]
+    fn wake_and_retain(&self) {
+        self.advance();
+        self.wakers.wake_and_retain()
+    }
+}
+
+impl<W: WakersRef> WakersRef for GenerationWakers<W> {
+    #[inline]
+    fn wake_by_ref(&self) {
+        self.advance();
+        self.wakers.wake_by_ref()
+    }
+
+    #[inline]
+    fn wake_n_by_ref(&self, n: usize) -> usize {
+        self.advance();
+        self.wakers.wake_n_by_ref(n)
+    }
+
+    #[inline]
+    fn wake_and_retain_by_ref(&self) {
+        self.advance();
+        self.wakers.wake_and_retain_by_ref()
+    }
+}
+
+impl<W: Wakers> Wakers for GenerationWakers<W> {
+    #[inline]
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+        self.wakers.try_pend_by_ref(waker)
+    }
+
+    #[inline]
+    fn remove_by_ref(&self, waker: &Waker) -> bool {
+        self.wakers.remove_by_ref(waker)
+    }
+}
+
+impl<W: RegisterMut> RegisterMut for GenerationWakers<W> {
+    type Key = W::Key;
+
+    #[inline]
+    fn register_key_evicting(&mut self, key: Option<W::Key>, waker: &Waker) -> (Option<W::Key>, Self::Batch) {
+        self.wakers.register_key_evicting(key, waker)
+    }
+
+    #[inline]
+    fn deregister_key(&mut self, key: W::Key) -> bool {
+        self.wakers.deregister_key(key)
+    }
+}
+
+impl<W: Register> Register for GenerationWakers<W> {
+    type Key = W::Key;
+
+    #[inline]
+    fn register_key_by_ref(&self, key: Option<W::Key>, waker: &Waker) -> Option<W::Key> {
+        self.wakers.register_key_by_ref(key, waker)
+    }
+
+    #[inline]
+    fn deregister_key_by_ref(&self, key: W::Key) -> bool {
+        self.wakers.deregister_key_by_ref(key)
+    }
+}
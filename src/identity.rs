@@ -0,0 +1,76 @@
+use core::task::Waker;
+#[cfg(feature = "std")]
+use std::collections::HashMap as Map;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as Map;
+
+/// The raw data pointer and vtable of a waker, which is exactly what [`Waker::will_wake`] compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Identity {
+    data: usize,
+    vtable: usize,
+}
+
+impl Identity {
+    #[inline]
+    fn of(waker: &Waker) -> Self {
+        Self {
+            data: waker.data() as usize,
+            vtable: waker.vtable() as *const _ as usize,
+        }
+    }
+}
+
+/// Counts stored wakers by identity, so growable storage can dedup without scanning every waker.
+///
+/// With `std` the index is a hash map, so a lookup takes expected O(1) time however many wakers are
+/// stored. With only `alloc` it's a `BTreeMap`, which takes O(log n). Keyed registrations may store
+/// the same waker more than once, hence the counts.
+///
+/// Always index the clone that's actually stored, not the waker it was cloned from. The two normally
+/// share an identity, but nothing guarantees that (under Miri, for one, a clone can carry a different
+/// vtable address). An entry whose removal didn't match would linger, and make every later pend of
+/// that waker look like a duplicate, which is then never stored.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IdentityIndex {
+    /// Only allocated once something is inserted, keeping construction `const`.
+    counts: Option<Map<Identity, usize>>,
+}
+
+impl IdentityIndex {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            counts: None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, waker: &Waker) -> bool {
+        self.counts.as_ref().is_some_and(|counts| counts.contains_key(&Identity::of(waker)))
+    }
+
+    #[inline]
+    pub(crate) fn insert(&mut self, waker: &Waker) {
+        *self.counts.get_or_insert_with(Map::new).entry(Identity::of(waker)).or_insert(0) += 1;
+    }
+
+    pub(crate) fn remove(&mut self, waker: &Waker) {
+        let identity = Identity::of(waker);
+        if let Some(counts) = &mut self.counts {
+            if let Some(count) = counts.get_mut(&identity) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&identity);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        if let Some(counts) = &mut self.counts {
+            counts.clear()
+        }
+    }
+}
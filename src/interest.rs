@@ -0,0 +1,163 @@
+use core::task::Waker;
+use core::{array, ops};
+use super::{WakerQueue, WakersMut, PendOutcome};
+
+/// A set of readiness classes that a waiter is interested in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Interest(u8);
+
+impl Interest {
+    pub const READABLE: Self = Self(1 << 0);
+    pub const WRITABLE: Self = Self(1 << 1);
+    pub const ERROR: Self = Self(1 << 2);
+
+    /// The number of distinct classes, including the ones left for custom use.
+    pub const CLASSES: usize = 8;
+
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// A custom class, for `bit` in `3..8`.
+    ///
+    /// # Panics
+    ///
+    /// If `bit` is outside that range, as the lower bits belong to the built-in classes.
+    #[inline]
+    pub const fn custom(bit: u32) -> Self {
+        assert!(bit >= 3 && bit < Self::CLASSES as u32, "custom interest bits must be in 3..8");
+        Self(1 << bit)
+    }
+
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    fn classes(self) -> impl Iterator<Item = usize> {
+        (0..Self::CLASSES).filter(move |&i| self.0 & (1 << i) != 0)
+    }
+}
+
+impl ops::BitOr for Interest {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for Interest {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+/// Waker storage split by readiness [`Interest`], with a separate `W` for each class.
+///
+/// A waiter registered for several classes is stored in each of them, and may be woken once per class
+/// if several become ready at once.
+///
+/// [`wake_ready`](InterestWakers::wake_ready) wakes in place. When this is guarded by a lock, use
+/// [`take_ready`](InterestWakers::take_ready) instead, and wake what it returns once the lock is
+/// released.
+#[derive(Debug, Clone, Default)]
+pub struct InterestWakers<W = WakerQueue> {
+    classes: [W; Interest::CLASSES],
+}
+
+impl<W> InterestWakers<W> {
+    #[inline]
+    pub const fn from_classes(classes: [W; Interest::CLASSES]) -> Self {
+        Self {
+            classes,
+        }
+    }
+}
+
+impl<W: Default> InterestWakers<W> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<W: WakersMut> InterestWakers<W> {
+    /// Registers `waker` with every class in `interest`.
+    ///
+    /// Reports the least favourable outcome across those classes, so [`PendOutcome::Full`] means at
+    /// least one of them didn't store it. An empty `interest` stores nothing, and so reports `Full`
+    /// too.
+    pub fn pend_interest(&mut self, interest: Interest, waker: &Waker) -> PendOutcome {
+        let (outcome, evicted) = self.pend_interest_evicting(interest, waker);
+        evicted.for_each(Waker::wake);
+        outcome
+    }
+
+    /// Like [`pend_interest`](InterestWakers::pend_interest), but hands back anything evicted from
+    /// those classes rather than waking it.
+    pub fn pend_interest_evicting(&mut self, interest: Interest, waker: &Waker) -> (PendOutcome, impl Iterator<Item = Waker>) {
+        let mut outcome = if interest.is_empty() {
+            PendOutcome::Full
+        } else {
+            PendOutcome::AlreadyRegistered
+        };
+        let evicted: [_; Interest::CLASSES] = array::from_fn(|i| {
+            if !interest.intersects(Interest(1 << i)) {
+                return None
+            }
+
+            let (pended, evicted) = self.classes[i].try_pend_evicting(waker);
+            outcome = match (outcome, pended) {
+                (PendOutcome::Full, _) | (_, PendOutcome::Full) => PendOutcome::Full,
+                (PendOutcome::Evicted, _) | (_, PendOutcome::Evicted) => PendOutcome::Evicted,
+                (PendOutcome::Inserted, _) | (_, PendOutcome::Inserted) => PendOutcome::Inserted,
+                _ => PendOutcome::AlreadyRegistered,
+            };
+            Some(evicted)
+        });
+        (outcome, IntoIterator::into_iter(evicted).flatten().flatten())
+    }
+
+    /// Removes the waiters of every class in `ready` without waking them.
+    pub fn take_ready(&mut self, ready: Interest) -> impl Iterator<Item = Waker> {
+        let taken: [_; Interest::CLASSES] = array::from_fn(|i| {
+            if ready.intersects(Interest(1 << i)) {
+                Some(self.classes[i].take_all())
+            } else {
+                None
+            }
+        });
+        IntoIterator::into_iter(taken).flatten().flatten()
+    }
+
+    /// Wakes the waiters of every class in `ready`.
+    pub fn wake_ready(&mut self, ready: Interest) {
+        for i in ready.classes() {
+            self.classes[i].wake()
+        }
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for InterestWakers<W> {
+    const DEFAULT: Self = Self::from_classes([W::DEFAULT; Interest::CLASSES]);
+}
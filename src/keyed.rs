@@ -0,0 +1,190 @@
+use core::task::Waker;
+use core::{mem, fmt};
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+use super::{WakersMut, PendOutcome};
+
+/// Per-key waker storage, so that only the tasks waiting on one resource get woken.
+///
+/// Each key gets its own `W`, created on first use and dropped once that key is woken.
+///
+/// [`wake_key`](KeyedWakers::wake_key) and [`wake_all`](KeyedWakers::wake_all) wake in place. When
+/// this is guarded by a lock, use [`take_key`](KeyedWakers::take_key) or
+/// [`take_all`](KeyedWakers::take_all) instead, and wake what they return once the lock is released.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct KeyedWakers<K, W> {
+    wakers: BTreeMap<K, W>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K, W> KeyedWakers<K, W> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            wakers: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, W: WakersMut + Default> KeyedWakers<K, W> {
+    pub fn pend_key(&mut self, key: K, waker: &Waker) -> PendOutcome {
+        self.wakers.entry(key).or_default().try_pend(waker)
+    }
+
+    /// Like [`pend_key`](KeyedWakers::pend_key), but hands back anything evicted from the key's
+    /// storage rather than waking it.
+    pub fn pend_key_evicting(&mut self, key: K, waker: &Waker) -> (PendOutcome, W::Batch) {
+        self.wakers.entry(key).or_default().try_pend_evicting(waker)
+    }
+
+    /// Removes the storage for `key` without waking it, so it can be woken after releasing any locks.
+    #[inline]
+    pub fn take_key(&mut self, key: &K) -> Option<W> {
+        self.wakers.remove(key)
+    }
+
+    #[inline]
+    pub fn wake_key(&mut self, key: &K) {
+        if let Some(mut w) = self.take_key(key) {
+            w.wake()
+        }
+    }
+
+    /// Removes the storage for every key without waking it.
+    #[inline]
+    pub fn take_all(&mut self) -> impl Iterator<Item = W> {
+        mem::take(&mut self.wakers).into_values()
+    }
+
+    pub fn wake_all(&mut self) {
+        for mut w in self.take_all() {
+            w.wake()
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, W> Default for KeyedWakers<K, W> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "const-default"))]
+impl<K, W> const_default::ConstDefault for KeyedWakers<K, W> {
+    const DEFAULT: Self = Self::new();
+}
+
+/// Like `KeyedWakers`, but backed by a fixed array of `N` keys for use without an allocator.
+///
+/// Pending under a new key while all `N` are in use reports [`PendOutcome::Full`]. As with
+/// `KeyedWakers`, [`take_key`](ArrayKeyedWakers::take_key) and
+/// [`take_all`](ArrayKeyedWakers::take_all) leave the waking to the caller, say once a lock guarding
+/// this has been released.
+pub struct ArrayKeyedWakers<K, W, const N: usize> {
+    wakers: [Option<(K, W)>; N],
+}
+
+impl<K, W, const N: usize> ArrayKeyedWakers<K, W, N> {
+    const VACANT: Option<(K, W)> = None;
+
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            wakers: [Self::VACANT; N],
+        }
+    }
+}
+
+impl<K: PartialEq, W: WakersMut + Default, const N: usize> ArrayKeyedWakers<K, W, N> {
+    pub fn pend_key(&mut self, key: K, waker: &Waker) -> PendOutcome {
+        match self.storage(key) {
+            Some(w) => w.try_pend(waker),
+            None => PendOutcome::Full,
+        }
+    }
+
+    /// Like [`pend_key`](ArrayKeyedWakers::pend_key), but hands back anything evicted from the key's
+    /// storage rather than waking it.
+    pub fn pend_key_evicting(&mut self, key: K, waker: &Waker) -> (PendOutcome, W::Batch) {
+        match self.storage(key) {
+            Some(w) => w.try_pend_evicting(waker),
+            None => (PendOutcome::Full, W::default().take_all()),
+        }
+    }
+
+    /// The storage for `key`, created if it's not in use yet and there's room for it.
+    fn storage(&mut self, key: K) -> Option<&mut W> {
+        let i = match self.position(&key) {
+            Some(i) => i,
+            None => {
+                let i = self.wakers.iter().position(|slot| slot.is_none())?;
+                self.wakers[i] = Some((key, W::default()));
+                i
+            },
+        };
+        self.wakers[i].as_mut().map(|(_, w)| w)
+    }
+
+    /// Removes the storage for `key` without waking it, so it can be woken after releasing any locks.
+    #[inline]
+    pub fn take_key(&mut self, key: &K) -> Option<W> {
+        let i = self.position(key)?;
+        self.wakers[i].take().map(|(_, w)| w)
+    }
+
+    #[inline]
+    pub fn wake_key(&mut self, key: &K) {
+        if let Some(mut w) = self.take_key(key) {
+            w.wake()
+        }
+    }
+
+    /// Removes the storage for every key without waking it.
+    #[inline]
+    pub fn take_all(&mut self) -> impl Iterator<Item = W> {
+        IntoIterator::into_iter(mem::replace(&mut self.wakers, [Self::VACANT; N])).flatten().map(|(_, w)| w)
+    }
+
+    pub fn wake_all(&mut self) {
+        for mut w in self.take_all() {
+            w.wake()
+        }
+    }
+
+    fn position(&self, key: &K) -> Option<usize> {
+        self.wakers.iter().position(|slot| matches!(slot, Some((k, _)) if k == key))
+    }
+}
+
+impl<K, W, const N: usize> Default for ArrayKeyedWakers<K, W, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, W: Clone, const N: usize> Clone for ArrayKeyedWakers<K, W, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            wakers: self.wakers.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, W: fmt::Debug, const N: usize> fmt::Debug for ArrayKeyedWakers<K, W, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.wakers.iter().flatten().map(|(k, w)| (k, w)))
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<K, W, const N: usize> const_default::ConstDefault for ArrayKeyedWakers<K, W, N> {
+    const DEFAULT: Self = Self::new();
+}
@@ -1,74 +1,425 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(feature = "slab")]
-compile_error!("TODO slab support");
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use core::task::Waker;
-use core::cell::UnsafeCell;
-use core::{ptr, mem, fmt};
+use core::cell::{UnsafeCell, Cell};
+use core::marker::PhantomData;
+use core::{mem, fmt, iter, array};
 
+/// Wakes a container through a shared reference.
+///
+/// Like [`WakersMut::wake`], [`wake_by_ref`](WakersRef::wake_by_ref) clears everything it wakes:
+/// a woken task must pend again if it wants another wakeup.
 pub trait WakersRef {
     fn wake_by_ref(&self);
+
+    /// An explicit alias for [`wake_by_ref`](WakersRef::wake_by_ref).
+    #[inline]
+    fn wake_and_clear_by_ref(&self) {
+        self.wake_by_ref()
+    }
+
+    /// Wakes everything, but leaves it all registered.
+    fn wake_and_retain_by_ref(&self);
+
+    /// Wakes up to `n` of the longest-waiting wakers, returning how many were woken.
+    fn wake_n_by_ref(&self, n: usize) -> usize;
+
+    #[inline]
+    fn wake_one_by_ref(&self) -> bool {
+        self.wake_n_by_ref(1) != 0
+    }
 }
 
 pub trait Wakers: WakersRef {
-    fn pend_by_ref(&self, waker: &Waker);
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome;
+
+    /// The shared-reference counterpart to [`WakersMut::remove`].
+    fn remove_by_ref(&self, waker: &Waker) -> bool;
+
+    /// Registers `waker`, waking it immediately if it couldn't be stored.
+    #[inline]
+    fn pend_by_ref(&self, waker: &Waker) {
+        if self.try_pend_by_ref(waker) == PendOutcome::Full {
+            waker.wake_by_ref()
+        }
+    }
 }
 
 pub trait WakersMut {
-    fn pend(&mut self, waker: &Waker);
-    fn wake(&mut self);
-}
+    /// Like [`try_pend`](WakersMut::try_pend), but hands back any wakers evicted to make room
+    /// instead of waking them, so they can be woken after any locks guarding the container have been
+    /// released.
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch);
 
-// TODO parameterize by backing storage array size
-#[derive(Debug, Clone, Default)]
-pub struct WakerQueue {
-    waker: Option<Waker>,
-}
+    /// Registers `waker`, waking anything the container's overflow policy evicts to make room.
+    #[inline]
+    fn try_pend(&mut self, waker: &Waker) -> PendOutcome {
+        let (outcome, evicted) = self.try_pend_evicting(waker);
+        for w in evicted {
+            w.wake()
+        }
+        outcome
+    }
 
-impl WakersMut for WakerQueue {
+    /// Registers `waker`, waking it immediately if it couldn't be stored.
+    #[inline]
     fn pend(&mut self, waker: &Waker) {
-        for w in &self.waker {
-            if w.will_wake(waker) {
-                return
-            }
+        if self.try_pend(waker) == PendOutcome::Full {
+            waker.wake_by_ref()
         }
+    }
+
+    /// Wakers removed from the container by [`take_all`](WakersMut::take_all), or evicted by
+    /// [`try_pend_evicting`](WakersMut::try_pend_evicting).
+    type Batch: IntoIterator<Item = Waker>;
+
+    /// Removes every stored waker without waking them, so they can be woken after any locks
+    /// guarding the container have been released.
+    fn take_all(&mut self) -> Self::Batch;
+
+    /// Like [`take_all`](WakersMut::take_all), but only removes up to `n` of the longest-waiting wakers.
+    fn take_n(&mut self, n: usize) -> Self::Batch;
+
+    /// Clones every stored waker, leaving them registered.
+    fn clone_all(&self) -> Self::Batch;
+
+    /// Drops a stored waker that [`will_wake`](Waker::will_wake) the same task as `waker`, without
+    /// waking it. Returns whether one was found.
+    fn remove(&mut self, waker: &Waker) -> bool;
+
+    /// How many wakers are currently registered.
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many wakers can be registered at once, or `None` if the storage grows as needed.
+    fn capacity(&self) -> Option<usize>;
 
-        if let Some(w) = self.waker.take() {
-            // we ran out of space, just start going wild...
+    /// Wakes and clears everything.
+    #[inline]
+    fn wake(&mut self) {
+        for w in self.take_all() {
             w.wake()
         }
-        self.waker = Some(waker.clone());
     }
 
-    fn wake(&mut self) {
-        if let Some(w) = self.waker.take() {
+    /// An explicit alias for [`wake`](WakersMut::wake).
+    #[inline]
+    fn wake_and_clear(&mut self) {
+        self.wake()
+    }
+
+    /// Wakes everything, but leaves it all registered.
+    #[inline]
+    fn wake_and_retain(&self) {
+        for w in self.clone_all() {
             w.wake()
         }
     }
+
+    /// Wakes up to `n` of the longest-waiting wakers, returning how many were woken.
+    #[inline]
+    fn wake_n(&mut self, n: usize) -> usize {
+        let mut woken = 0;
+        for w in self.take_n(n) {
+            w.wake();
+            woken += 1;
+        }
+        woken
+    }
+
+    #[inline]
+    fn wake_one(&mut self) -> bool {
+        self.wake_n(1) != 0
+    }
 }
 
-impl WakersRef for WakerQueue {
-    fn wake_by_ref(&self) {
-        for w in &self.waker {
-            w.wake_by_ref()
+/// Storage that hands out a key for each registration, so it can later be withdrawn without waking.
+///
+/// Unlike [`pend`](WakersMut::pend), registrations are never deduplicated against each other, so
+/// withdrawing one can't take another registration of the same task down with it.
+pub trait RegisterMut: WakersMut {
+    type Key: Copy;
+
+    /// Stores `waker` in place of the entry under `key` if it's still registered, or in a new entry
+    /// otherwise. Returns the key of the entry, or `None` if there was no room for it, along with any
+    /// wakers evicted to make room, which are left for the caller to wake.
+    fn register_key_evicting(&mut self, key: Option<Self::Key>, waker: &Waker) -> (Option<Self::Key>, Self::Batch);
+
+    /// Like [`register_key_evicting`](RegisterMut::register_key_evicting), but wakes whatever was
+    /// evicted straight away.
+    #[inline]
+    fn register_key(&mut self, key: Option<Self::Key>, waker: &Waker) -> Option<Self::Key> {
+        let (key, evicted) = self.register_key_evicting(key, waker);
+        for w in evicted {
+            w.wake()
         }
+        key
+    }
+
+    /// Removes the entry under `key` without waking it, returning whether it was still registered.
+    fn deregister_key(&mut self, key: Self::Key) -> bool;
+}
+
+/// The shared-reference counterpart to [`RegisterMut`].
+///
+/// Storage that only implements [`Wakers`] can be registered with through [`ByWaker`].
+pub trait Register: Wakers {
+    type Key;
+
+    fn register_key_by_ref(&self, key: Option<Self::Key>, waker: &Waker) -> Option<Self::Key>;
+
+    fn deregister_key_by_ref(&self, key: Self::Key) -> bool;
+
+    /// Registers `waker`, returning a guard that withdraws the registration when dropped.
+    #[inline]
+    fn register(&self, waker: &Waker) -> Registration<'_, Self> {
+        let mut registration = Registration::new(self);
+        registration.pend(waker);
+        registration
     }
 }
 
-impl WakerQueue {
+mod registration;
+pub use registration::Registration;
+
+mod by_waker;
+pub use by_waker::ByWaker;
+
+/// What became of a waker handed to [`WakersMut::try_pend`] or [`Wakers::try_pend_by_ref`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendOutcome {
+    /// The waker was stored.
+    Inserted,
+    /// A waker that [`will_wake`](Waker::will_wake) the same task was already stored.
+    AlreadyRegistered,
+    /// The waker was stored, but pushed out previously stored wakers to make room.
+    Evicted,
+    /// There was no room, so the waker was not stored.
+    Full,
+}
+
+impl PendOutcome {
+    /// Whether the waker is now stored by the container.
+    #[inline]
+    pub fn is_registered(self) -> bool {
+        self != PendOutcome::Full
+    }
+}
+
+const NONE: Option<Waker> = None;
+
+pub mod overflow;
+use overflow::{OverflowPolicy, OverflowAction, WakeEvicted};
+
+/// Fixed-capacity waker storage, backed by an inline array of `N` slots.
+///
+/// Registered wakers are kept in the order they were pended, and are woken in that same order.
+/// What happens once all slots are taken is decided by the [`OverflowPolicy`] `P`.
+pub struct WakerQueue<const N: usize = 1, P = WakeEvicted> {
+    /// Occupied slots are always packed at the front.
+    wakers: [Option<Waker>; N],
+    /// The [`RegisterMut`] key of each occupied slot in `wakers`.
+    keys: [usize; N],
+    next_key: usize,
+    policy: PhantomData<P>,
+}
+
+impl<const N: usize, P: OverflowPolicy> WakerQueue<N, P> {
+    /// Stores `waker` without checking whether it's already registered, returning its new key along
+    /// with whatever had to be evicted to make room for it.
+    fn insert(&mut self, waker: &Waker) -> (Option<(usize, PendOutcome)>, <Self as WakersMut>::Batch) {
+        let key = self.next_key;
+        let mut evicted = [NONE; N];
+
+        let outcome = if let Some(i) = self.wakers.iter().position(|w| w.is_none()) {
+            self.wakers[i] = Some(waker.clone());
+            self.keys[i] = key;
+            PendOutcome::Inserted
+        } else if N == 0 {
+            return (None, self.take_all())
+        } else {
+            match P::on_overflow() {
+                OverflowAction::WakeEvicted => {
+                    // we ran out of space, just start going wild...
+                    evicted[0] = self.wakers[0].take();
+                    self.wakers.rotate_left(1);
+                    self.keys.rotate_left(1);
+                    self.wakers[N - 1] = Some(waker.clone());
+                    self.keys[N - 1] = key;
+                },
+                OverflowAction::WakeAll => {
+                    evicted = mem::replace(&mut self.wakers, [NONE; N]);
+                    self.wakers[0] = Some(waker.clone());
+                    self.keys[0] = key;
+                },
+                OverflowAction::Reject => return (None, IntoIterator::into_iter(evicted).flatten()),
+            }
+            PendOutcome::Evicted
+        };
+
+        self.next_key = key.wrapping_add(1);
+        (Some((key, outcome)), IntoIterator::into_iter(evicted).flatten())
+    }
+
+    fn position(&self, key: usize) -> Option<usize> {
+        self.wakers.iter().zip(&self.keys)
+            .position(|(w, &k)| w.is_some() && k == key)
+    }
+
+    fn remove_at(&mut self, i: usize) {
+        self.wakers[i] = None;
+        self.wakers[i..].rotate_left(1);
+        self.keys[i..].rotate_left(1);
+    }
+
+    /// Wakes everything, leaving it all registered, as `WakerQueue`'s old [`WakersRef`] impl did.
+    ///
+    /// That impl was dropped because [`WakersRef::wake_by_ref`] now clears what it wakes, which can't
+    /// be done through a shared reference here.
+    #[deprecated(note = "use `WakersMut::wake_and_retain`, or `wake` to also clear the queue")]
+    #[inline]
+    pub fn wake_by_ref(&self) {
+        self.wake_and_retain()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> WakersMut for WakerQueue<N, P> {
+    type Batch = iter::Flatten<array::IntoIter<Option<Waker>, N>>;
+
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        if self.wakers.iter().flatten().any(|w| w.will_wake(waker)) {
+            return (PendOutcome::AlreadyRegistered, IntoIterator::into_iter([NONE; N]).flatten())
+        }
+
+        match self.insert(waker) {
+            (Some((_, outcome)), evicted) => (outcome, evicted),
+            (None, evicted) => (PendOutcome::Full, evicted),
+        }
+    }
+
+    fn take_all(&mut self) -> Self::Batch {
+        IntoIterator::into_iter(mem::replace(&mut self.wakers, [NONE; N])).flatten()
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let n = n.min(N);
+        let mut batch = [NONE; N];
+        for (taken, w) in batch.iter_mut().zip(&mut self.wakers[..n]) {
+            *taken = w.take();
+        }
+        self.wakers.rotate_left(n);
+        self.keys.rotate_left(n);
+        IntoIterator::into_iter(batch).flatten()
+    }
+
+    #[inline]
+    fn clone_all(&self) -> Self::Batch {
+        IntoIterator::into_iter(self.wakers.clone()).flatten()
+    }
+
+    fn remove(&mut self, waker: &Waker) -> bool {
+        match self.wakers.iter().position(|w| w.as_ref().is_some_and(|w| w.will_wake(waker))) {
+            Some(i) => {
+                self.remove_at(i);
+                true
+            },
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.wakers.iter().take_while(|w| w.is_some()).count()
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> RegisterMut for WakerQueue<N, P> {
+    type Key = usize;
+
+    fn register_key_evicting(&mut self, key: Option<usize>, waker: &Waker) -> (Option<usize>, Self::Batch) {
+        if let Some(i) = key.and_then(|key| self.position(key)) {
+            if let Some(w) = &mut self.wakers[i] {
+                if !w.will_wake(waker) {
+                    *w = waker.clone();
+                }
+            }
+            return (key, IntoIterator::into_iter([NONE; N]).flatten())
+        }
+
+        let (inserted, evicted) = self.insert(waker);
+        (inserted.map(|(key, _)| key), evicted)
+    }
+
+    fn deregister_key(&mut self, key: usize) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                self.remove_at(i);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+impl<const N: usize, P> WakerQueue<N, P> {
+    /// Iterates over the stored wakers, in the order they'll be woken.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Waker> {
+        self.wakers.iter().flatten()
+    }
+
     pub const fn new() -> Self {
         Self {
-            waker: None,
+            wakers: [NONE; N],
+            keys: [0; N],
+            next_key: 0,
+            policy: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, P> Default for WakerQueue<N, P> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, P> Clone for WakerQueue<N, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            wakers: self.wakers.clone(),
+            keys: self.keys,
+            next_key: self.next_key,
+            policy: PhantomData,
         }
     }
 }
 
+impl<const N: usize, P> fmt::Debug for WakerQueue<N, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WakerQueue")
+            .field("wakers", &self.wakers)
+            .finish()
+    }
+}
+
 #[cfg(feature = "const-default")]
-impl const_default::ConstDefault for WakerQueue {
-    const DEFAULT: Self = Self {
-        waker: None,
-    };
+impl<const N: usize, P> const_default::ConstDefault for WakerQueue<N, P> {
+    const DEFAULT: Self = Self::new();
 }
 
 #[derive(Default)]
@@ -76,12 +427,27 @@ pub struct SendWakers<W> {
     /// # Safety
     ///
     /// Relies on the container not being Sync, and never exposing a shared reference to the inner data.
-    ///
-    /// ## Caveat
-    ///
-    /// Don't make a circular reference to one of these with a rawwaker, please?
-    /// (waker vtables are an unsafe API so does this technically even count as a safety hole?)
+    /// Every access goes through [`SendWakers::borrow`], so a waker that calls back into this container
+    /// from inside one of its own methods can't alias it.
     wakers: UnsafeCell<W>,
+    borrowed: Cell<bool>,
+    /// How many wakeups arrived while `wakers` was borrowed, delivered once the borrow ends.
+    /// `usize::MAX` stands in for a `wake_by_ref`.
+    deferred_wakes: Cell<usize>,
+    /// Whether a `wake_and_retain_by_ref` arrived while `wakers` was borrowed.
+    deferred_retain: Cell<bool>,
+    /// Delivers deferred wakeups once the borrow ends. Set by whichever method deferred them, since
+    /// only it knows how to wake the storage.
+    flush: Cell<Option<fn(&Self)>>,
+}
+
+struct Borrow<'a>(&'a Cell<bool>);
+
+impl Drop for Borrow<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.set(false)
+    }
 }
 
 impl<W> SendWakers<W> {
@@ -89,61 +455,131 @@ impl<W> SendWakers<W> {
     pub const fn new(wakers: W) -> Self {
         Self {
             wakers: UnsafeCell::new(wakers),
+            borrowed: Cell::new(false),
+            deferred_wakes: Cell::new(0),
+            deferred_retain: Cell::new(false),
+            flush: Cell::new(None),
         }
     }
 
+    /// Runs `f` with exclusive access to the inner storage, or returns `None` if it's already in use
+    /// further up the stack. Any wakeups deferred in the meantime are delivered once the borrow ends.
     #[inline]
-    unsafe fn inner(&self) -> &W {
-        &*self.wakers.get()
-    }
+    fn borrow<R, F: FnOnce(&mut W) -> R>(&self, f: F) -> Option<R> {
+        if self.borrowed.replace(true) {
+            return None
+        }
 
-    #[inline]
-    unsafe fn inner_mut(&self) -> &mut W {
-        &mut *self.wakers.get()
+        let res = {
+            let _borrow = Borrow(&self.borrowed);
+            f(unsafe { &mut *self.wakers.get() })
+        };
+        if let Some(flush) = self.flush.take() {
+            flush(self)
+        }
+        Some(res)
     }
 
     #[inline]
     pub fn get_mut(&mut self) -> &mut W {
-        unsafe {
-            self.inner_mut()
-        }
+        self.wakers.get_mut()
     }
 
     #[inline]
     pub fn into_inner(self) -> W {
-        let res = unsafe { ptr::read(self.wakers.get()) };
-        mem::forget(self);
-        res
+        self.wakers.into_inner()
+    }
+
+    /// Looks at the storage, say to [`iter`](WakerQueue::iter) over it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within another method of the same container, such as from a waker's
+    /// `clone` or `drop`.
+    pub fn inspect<R, F: FnOnce(&W) -> R>(&self, f: F) -> R {
+        self.borrow(|w| f(w)).expect("SendWakers accessed while already in use")
+    }
+}
+
+impl<W: WakersMut> SendWakers<W> {
+    /// Delivers any wakeups that were deferred while the storage was borrowed.
+    fn wake_deferred(&self) {
+        if self.deferred_retain.replace(false) {
+            self.wake_and_retain_by_ref()
+        }
+        match self.deferred_wakes.replace(0) {
+            0 => (),
+            usize::MAX => self.wake_by_ref(),
+            n => {
+                self.wake_n_by_ref(n);
+            },
+        }
     }
 }
 
 impl<W: fmt::Debug> fmt::Debug for SendWakers<W> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(unsafe { self.inner() }, f)
+        match self.borrow(|w| fmt::Debug::fmt(w, f)) {
+            Some(res) => res,
+            None => f.write_str("SendWakers { <borrowed> }"),
+        }
     }
 }
 
 impl<W: Clone> Clone for SendWakers<W> {
     #[inline]
     fn clone(&self) -> Self {
-        Self::new(unsafe { self.inner() }.clone())
+        Self::new(self.borrow(|w| w.clone()).expect("SendWakers cloned while already in use"))
     }
 }
 
 #[cfg(feature = "const-default")]
 impl<W: const_default::ConstDefault> const_default::ConstDefault for SendWakers<W> {
-    const DEFAULT: Self = Self {
-        wakers: const_default::ConstDefault::DEFAULT,
-    };
+    const DEFAULT: Self = Self::new(W::DEFAULT);
 }
 
 unsafe impl<W: Send> Send for SendWakers<W> { }
 
 impl<W: WakersMut> WakersMut for SendWakers<W> {
+    type Batch = W::Batch;
+
     #[inline]
-    fn pend(&mut self, waker: &Waker) {
-        self.get_mut().pend(waker)
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        self.get_mut().try_pend_evicting(waker)
+    }
+
+    #[inline]
+    fn take_all(&mut self) -> Self::Batch {
+        self.get_mut().take_all()
+    }
+
+    #[inline]
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        self.get_mut().take_n(n)
+    }
+
+    /// Panics if called from within another method of the same container.
+    #[inline]
+    fn clone_all(&self) -> Self::Batch {
+        self.inspect(|w| w.clone_all())
+    }
+
+    #[inline]
+    fn remove(&mut self, waker: &Waker) -> bool {
+        self.get_mut().remove(waker)
+    }
+
+    /// Panics if called from within another method of the same container.
+    #[inline]
+    fn len(&self) -> usize {
+        self.inspect(|w| w.len())
+    }
+
+    /// Panics if called from within another method of the same container.
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        self.inspect(|w| w.capacity())
     }
 
     #[inline]
@@ -153,70 +589,295 @@ impl<W: WakersMut> WakersMut for SendWakers<W> {
 }
 
 impl<W: WakersMut> WakersRef for SendWakers<W> {
+    /// Wakes outside of the borrow, so wakers are free to re-register with this container.
+    /// Calling this from within another method of the same container defers the wake until that
+    /// method returns.
     #[inline]
     fn wake_by_ref(&self) {
-        unsafe { self.inner_mut() }.wake()
+        match self.borrow(|w| w.take_all()) {
+            Some(batch) => for w in batch {
+                w.wake()
+            },
+            None => {
+                self.deferred_wakes.set(usize::MAX);
+                self.flush.set(Some(Self::wake_deferred));
+            },
+        }
+    }
+
+    fn wake_n_by_ref(&self, n: usize) -> usize {
+        match self.borrow(|w| w.take_n(n)) {
+            Some(batch) => {
+                let mut woken = 0;
+                for w in batch {
+                    w.wake();
+                    woken += 1;
+                }
+                woken
+            },
+            None => {
+                self.deferred_wakes.set(self.deferred_wakes.get().saturating_add(n));
+                self.flush.set(Some(Self::wake_deferred));
+                0
+            },
+        }
+    }
+
+    fn wake_and_retain_by_ref(&self) {
+        match self.borrow(|w| w.clone_all()) {
+            Some(batch) => for w in batch {
+                w.wake()
+            },
+            None => {
+                self.deferred_retain.set(true);
+                self.flush.set(Some(Self::wake_deferred));
+            },
+        }
     }
 }
 
 impl<W: WakersMut> Wakers for SendWakers<W> {
+    /// Pending from within another method of the same container reports [`PendOutcome::Full`].
+    /// Anything evicted to make room is woken outside of the borrow.
     #[inline]
-    fn pend_by_ref(&self, waker: &Waker) {
-        unsafe { self.inner_mut() }.pend(waker)
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+        match self.borrow(|w| w.try_pend_evicting(waker)) {
+            Some((outcome, evicted)) => {
+                for w in evicted {
+                    w.wake()
+                }
+                outcome
+            },
+            None => PendOutcome::Full,
+        }
+    }
+
+    /// Removals from within another method of the same container find nothing.
+    #[inline]
+    fn remove_by_ref(&self, waker: &Waker) -> bool {
+        self.borrow(|w| w.remove(waker)).unwrap_or(false)
+    }
+}
+
+impl<W: RegisterMut> RegisterMut for SendWakers<W> {
+    type Key = W::Key;
+
+    #[inline]
+    fn register_key_evicting(&mut self, key: Option<W::Key>, waker: &Waker) -> (Option<W::Key>, Self::Batch) {
+        self.get_mut().register_key_evicting(key, waker)
+    }
+
+    #[inline]
+    fn deregister_key(&mut self, key: W::Key) -> bool {
+        self.get_mut().deregister_key(key)
+    }
+}
+
+impl<W: RegisterMut> Register for SendWakers<W> {
+    type Key = W::Key;
+
+    /// Registering from within another method of the same container fails as if there were no room.
+    #[inline]
+    fn register_key_by_ref(&self, key: Option<W::Key>, waker: &Waker) -> Option<W::Key> {
+        let (key, evicted) = self.borrow(|w| w.register_key_evicting(key, waker))?;
+        for w in evicted {
+            w.wake()
+        }
+        key
+    }
+
+    #[inline]
+    fn deregister_key_by_ref(&self, key: W::Key) -> bool {
+        self.borrow(|w| w.deregister_key(key)).unwrap_or(false)
     }
 }
 
-#[cfg(feature = "std")]
 mod sync_wakers {
-    use std::task::Waker;
-    use std::sync::Mutex;
-    use std::fmt;
-    use super::{Wakers, WakersRef, WakersMut};
+    use core::task::Waker;
+    use core::marker::PhantomData;
+    use core::fmt;
+    #[cfg(feature = "std")]
+    use std::sync::PoisonError;
+    use super::{Wakers, WakersRef, WakersMut, PendOutcome, Register, RegisterMut};
+
+    /// A mutual exclusion primitive that [`SyncWakers`] can guard its storage with.
+    pub trait Lock<T> {
+        fn new(value: T) -> Self;
+        fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R;
+        fn get_mut(&mut self) -> &mut T;
+        fn into_inner(self) -> T;
+    }
+
+    /// Recovers from poisoning rather than propagating the panic.
+    ///
+    /// Waker storage is never left in a state worse than "some wakers were dropped", so a task that
+    /// panicked mid-wake (say, a waker with a panicking `wake`) shouldn't take every other waiter
+    /// down with it.
+    #[cfg(feature = "std")]
+    impl<T> Lock<T> for std::sync::Mutex<T> {
+        #[inline]
+        fn new(value: T) -> Self {
+            std::sync::Mutex::new(value)
+        }
+
+        #[inline]
+        fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+            let mut guard = self.lock().unwrap_or_else(|e| {
+                self.clear_poison();
+                e.into_inner()
+            });
+            f(&mut guard)
+        }
+
+        #[inline]
+        fn get_mut(&mut self) -> &mut T {
+            self.get_mut().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        #[inline]
+        fn into_inner(self) -> T {
+            self.into_inner().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    #[cfg(feature = "lock_api")]
+    impl<R: lock_api::RawMutex, T> Lock<T> for lock_api::Mutex<R, T> {
+        #[inline]
+        fn new(value: T) -> Self {
+            lock_api::Mutex::new(value)
+        }
+
+        #[inline]
+        fn lock<U, F: FnOnce(&mut T) -> U>(&self, f: F) -> U {
+            f(&mut self.lock())
+        }
+
+        #[inline]
+        fn get_mut(&mut self) -> &mut T {
+            self.get_mut()
+        }
+
+        #[inline]
+        fn into_inner(self) -> T {
+            self.into_inner()
+        }
+    }
+
+    /// A `Sync` container that guards its storage with a [`Lock`], `std::sync::Mutex` by default.
+    ///
+    /// The default lock shrugs off poisoning, so none of its methods (including `Debug` and `Clone`)
+    /// will panic just because some other thread panicked while holding it.
+    #[cfg(feature = "std")]
+    pub struct SyncWakers<W, L = std::sync::Mutex<W>> {
+        wakers: L,
+        storage: PhantomData<fn() -> W>,
+    }
 
-    // TODO a Vec-backed waker queue?
+    /// A `Sync` container that guards its storage with a [`Lock`].
+    #[cfg(not(feature = "std"))]
+    pub struct SyncWakers<W, L> {
+        wakers: L,
+        storage: PhantomData<fn() -> W>,
+    }
 
-    #[derive(Default)]
-    pub struct SyncWakers<W> {
-        wakers: Mutex<W>,
+    impl<W: Default, L: Lock<W>> Default for SyncWakers<W, L> {
+        #[inline]
+        fn default() -> Self {
+            Self::from_lock(L::new(W::default()))
+        }
     }
 
-    impl<W: Clone> Clone for SyncWakers<W> {
+    impl<W: Clone, L: Lock<W>> Clone for SyncWakers<W, L> {
         #[inline]
         fn clone(&self) -> Self {
-            Self::new(self.wakers.lock().unwrap().clone())
+            Self::from_lock(L::new(self.wakers.lock(|w| w.clone())))
         }
     }
 
-    impl<W: fmt::Debug> fmt::Debug for SyncWakers<W> {
+    impl<W: fmt::Debug, L: Lock<W>> fmt::Debug for SyncWakers<W, L> {
         #[inline]
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            fmt::Debug::fmt(&self.wakers.lock().unwrap(), f)
+            self.wakers.lock(|w| fmt::Debug::fmt(w, f))
         }
     }
 
+    #[cfg(feature = "std")]
     impl<W> SyncWakers<W> {
         #[inline]
         pub fn new(wakers: W) -> Self {
+            Self::from_lock(std::sync::Mutex::new(wakers))
+        }
+    }
+
+    impl<W, L> SyncWakers<W, L> {
+        #[inline]
+        pub const fn from_lock(lock: L) -> Self {
             Self {
-                wakers: Mutex::new(wakers),
+                wakers: lock,
+                storage: PhantomData,
             }
         }
+    }
 
+    #[cfg(feature = "const-default")]
+    impl<W, L: const_default::ConstDefault> const_default::ConstDefault for SyncWakers<W, L> {
+        const DEFAULT: Self = Self::from_lock(L::DEFAULT);
+    }
+
+    impl<W, L: Lock<W>> SyncWakers<W, L> {
         #[inline]
         pub fn get_mut(&mut self) -> &mut W {
-            self.wakers.get_mut().unwrap()
+            self.wakers.get_mut()
         }
 
         #[inline]
         pub fn into_inner(self) -> W {
-            self.wakers.into_inner().unwrap()
+            self.wakers.into_inner()
+        }
+
+        /// Looks at the storage under the lock, say to [`iter`](crate::WakerQueue::iter) over it.
+        #[inline]
+        pub fn inspect<R, F: FnOnce(&W) -> R>(&self, f: F) -> R {
+            self.wakers.lock(|w| f(w))
         }
     }
 
-    impl<W: WakersMut> WakersMut for SyncWakers<W> {
+    impl<W: WakersMut, L: Lock<W>> WakersMut for SyncWakers<W, L> {
+        type Batch = W::Batch;
+
+        #[inline]
+        fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+            self.get_mut().try_pend_evicting(waker)
+        }
+
+        #[inline]
+        fn take_all(&mut self) -> Self::Batch {
+            self.get_mut().take_all()
+        }
+
+        #[inline]
+        fn take_n(&mut self, n: usize) -> Self::Batch {
+            self.get_mut().take_n(n)
+        }
+
+        #[inline]
+        fn clone_all(&self) -> Self::Batch {
+            self.wakers.lock(|w| w.clone_all())
+        }
+
+        #[inline]
+        fn remove(&mut self, waker: &Waker) -> bool {
+            self.get_mut().remove(waker)
+        }
+
         #[inline]
-        fn pend(&mut self, waker: &Waker) {
-            self.get_mut().pend(waker)
+        fn len(&self) -> usize {
+            self.wakers.lock(|w| w.len())
+        }
+
+        #[inline]
+        fn capacity(&self) -> Option<usize> {
+            self.wakers.lock(|w| w.capacity())
         }
 
         #[inline]
@@ -225,19 +886,145 @@ mod sync_wakers {
         }
     }
 
-    impl<W: WakersMut> Wakers for SyncWakers<W> {
+    impl<W: WakersMut, L: Lock<W>> Wakers for SyncWakers<W, L> {
+        /// Anything evicted to make room is woken outside of the lock.
+        #[inline]
+        fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+            let (outcome, evicted) = self.wakers.lock(|w| w.try_pend_evicting(waker));
+            for w in evicted {
+                w.wake()
+            }
+            outcome
+        }
+
         #[inline]
-        fn pend_by_ref(&self, waker: &Waker) {
-            self.wakers.lock().unwrap().pend(waker)
+        fn remove_by_ref(&self, waker: &Waker) -> bool {
+            self.wakers.lock(|w| w.remove(waker))
         }
     }
 
-    impl<W: WakersMut> WakersRef for SyncWakers<W> {
+    impl<W: WakersMut, L: Lock<W>> WakersRef for SyncWakers<W, L> {
+        /// Wakes outside of the lock, so wakers are free to re-register with this container.
         #[inline]
         fn wake_by_ref(&self) {
-            self.wakers.lock().unwrap().wake()
+            for w in self.wakers.lock(|w| w.take_all()) {
+                w.wake()
+            }
+        }
+
+        #[inline]
+        fn wake_n_by_ref(&self, n: usize) -> usize {
+            let mut woken = 0;
+            for w in self.wakers.lock(|w| w.take_n(n)) {
+                w.wake();
+                woken += 1;
+            }
+            woken
+        }
+
+        #[inline]
+        fn wake_and_retain_by_ref(&self) {
+            for w in self.wakers.lock(|w| w.clone_all()) {
+                w.wake()
+            }
+        }
+    }
+
+    impl<W: RegisterMut, L: Lock<W>> RegisterMut for SyncWakers<W, L> {
+        type Key = W::Key;
+
+        #[inline]
+        fn register_key_evicting(&mut self, key: Option<W::Key>, waker: &Waker) -> (Option<W::Key>, Self::Batch) {
+            self.get_mut().register_key_evicting(key, waker)
+        }
+
+        #[inline]
+        fn deregister_key(&mut self, key: W::Key) -> bool {
+            self.get_mut().deregister_key(key)
+        }
+    }
+
+    impl<W: RegisterMut, L: Lock<W>> Register for SyncWakers<W, L> {
+        type Key = W::Key;
+
+        #[inline]
+        fn register_key_by_ref(&self, key: Option<W::Key>, waker: &Waker) -> Option<W::Key> {
+            let (key, evicted) = self.wakers.lock(|w| w.register_key_evicting(key, waker));
+            for w in evicted {
+                w.wake()
+            }
+            key
+        }
+
+        #[inline]
+        fn deregister_key_by_ref(&self, key: W::Key) -> bool {
+            self.wakers.lock(|w| w.deregister_key(key))
         }
     }
 }
-#[cfg(feature = "std")]
-pub use sync_wakers::SyncWakers;
+pub use sync_wakers::{SyncWakers, Lock};
+
+#[cfg(feature = "slab")]
+mod slab_wakers;
+#[cfg(feature = "slab")]
+pub use slab_wakers::{SlabWakers, SlabKey};
+
+#[cfg(feature = "alloc")]
+mod identity;
+
+#[cfg(feature = "alloc")]
+mod vec_wakers;
+#[cfg(feature = "alloc")]
+pub use vec_wakers::VecWakers;
+
+// these need compare-and-swap, which some targets (such as thumbv6m) don't have
+#[cfg(target_has_atomic = "ptr")]
+mod atomic_wakers;
+#[cfg(target_has_atomic = "ptr")]
+pub use atomic_wakers::AtomicWakerSlot;
+
+#[cfg(feature = "critical-section")]
+mod cs_wakers;
+#[cfg(feature = "critical-section")]
+pub use cs_wakers::{CsWakers, CsLock};
+
+mod waiter_list;
+pub use waiter_list::{WaiterList, Waiter, ListGuard, Unguarded};
+#[cfg(feature = "critical-section")]
+pub use waiter_list::CsGuard;
+
+#[cfg(target_has_atomic = "ptr")]
+mod permits;
+
+#[cfg(target_has_atomic = "ptr")]
+mod notify;
+#[cfg(target_has_atomic = "ptr")]
+pub use notify::{Notify, Notified};
+
+#[cfg(target_has_atomic = "ptr")]
+mod event;
+#[cfg(target_has_atomic = "ptr")]
+pub use event::{ManualResetEvent, ManualResetWait, AutoResetEvent, AutoResetWait};
+
+#[cfg(target_has_atomic = "ptr")]
+mod generation;
+#[cfg(target_has_atomic = "ptr")]
+pub use generation::{GenerationWakers, EpochChanged};
+
+mod keyed;
+#[cfg(feature = "alloc")]
+pub use keyed::KeyedWakers;
+pub use keyed::ArrayKeyedWakers;
+
+mod interest;
+pub use interest::{InterestWakers, Interest};
+
+mod priority;
+pub use priority::PriorityWakers;
+#[cfg(feature = "alloc")]
+pub use priority::PriorityHeapWakers;
+
+mod fan_out;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use fan_out::arc_waker;
+pub use fan_out::static_waker;
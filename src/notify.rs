@@ -0,0 +1,157 @@
+use core::future::Future;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+use core::pin::Pin;
+use core::{mem, fmt};
+use super::permits::Permits;
+use super::{Register, Registration};
+
+/// Notifies tasks waiting on it, built on top of any [`Register`] container, or any other
+/// [`Wakers`](crate::Wakers) storage wrapped in [`ByWaker`](crate::ByWaker).
+///
+/// [`notify_one`](Notify::notify_one) leaves a permit behind when nobody is waiting yet, and
+/// [`notify_waiters`](Notify::notify_waiters) bumps a generation counter that every outstanding
+/// [`Notified`] compares against, so a notification that lands between a task checking its condition
+/// and registering its waker is never lost.
+///
+/// Each [`Notified`] holds its own keyed [`Registration`] rather than pending a plain waker. Two of
+/// them polled by the same task would otherwise share one deduplicated entry, and the first to be
+/// dropped would withdraw the other's registration along with its own. Through `ByWaker` they do
+/// share one, so withdrawing it wakes the task to have the other put it back.
+pub struct Notify<W> {
+    wakers: W,
+    permits: Permits,
+    generation: AtomicUsize,
+}
+
+impl<W> Notify<W> {
+    #[inline]
+    pub const fn new(wakers: W) -> Self {
+        Self {
+            wakers,
+            permits: Permits::new(),
+            generation: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<W: Register> Notify<W> {
+    /// Returns a future that completes once this is notified.
+    ///
+    /// A [`notify_waiters`](Notify::notify_waiters) call made any time after this returns will
+    /// complete the future, even if it hasn't been polled yet.
+    #[inline]
+    pub fn notified(&self) -> Notified<'_, W> {
+        Notified {
+            notify: self,
+            generation: self.generation.load(Ordering::Acquire),
+            registration: Registration::new(&self.wakers),
+            registered: false,
+            done: false,
+        }
+    }
+
+    /// Wakes the longest-waiting task, handing it a permit.
+    ///
+    /// If nobody is waiting, the permit is kept for the next [`Notified`] to be polled instead. At most
+    /// one permit is kept this way; they don't accumulate. Permits handed to tasks that were woken but
+    /// haven't been polled yet don't count towards that.
+    pub fn notify_one(&self) {
+        self.permits.release(|| self.wakers.wake_one_by_ref())
+    }
+
+    /// Wakes every task currently waiting, along with any [`Notified`] that was created but not yet polled.
+    pub fn notify_waiters(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.wakers.wake_by_ref();
+    }
+}
+
+impl<W: Default> Default for Notify<W> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(W::default())
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for Notify<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("wakers", &self.wakers)
+            .field("permits", &self.permits)
+            .field("generation", &self.generation.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<W: const_default::ConstDefault> const_default::ConstDefault for Notify<W> {
+    const DEFAULT: Self = Self::new(W::DEFAULT);
+}
+
+/// The future returned by [`Notify::notified`].
+pub struct Notified<'a, W: Register> {
+    notify: &'a Notify<W>,
+    generation: usize,
+    registration: Registration<'a, W>,
+    registered: bool,
+    done: bool,
+}
+
+impl<W: Register> Unpin for Notified<'_, W> { }
+
+impl<W: Register> Notified<'_, W> {
+    fn try_complete(&mut self) -> bool {
+        self.done = self.notify.generation.load(Ordering::Acquire) != self.generation
+            || self.notify.permits.take();
+        self.done
+    }
+
+    /// Withdraws our registration, passing the permit along to another waiter if we were woken for
+    /// one that we never took.
+    fn release(&mut self) {
+        if mem::take(&mut self.registered) && !self.registration.deregister() && !self.done {
+            let wakers = &self.notify.wakers;
+            self.notify.permits.forward(|| wakers.wake_one_by_ref());
+        }
+    }
+}
+
+impl<W: Register> Future for Notified<'_, W> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(())
+        }
+
+        if !this.try_complete() {
+            this.registered = this.registration.pend(cx.waker());
+
+            // a notification may have slipped in before the registration landed
+            if !this.try_complete() {
+                return Poll::Pending
+            }
+        }
+
+        this.release();
+        Poll::Ready(())
+    }
+}
+
+impl<W: Register> Drop for Notified<'_, W> {
+    #[inline]
+    fn drop(&mut self) {
+        self.release()
+    }
+}
+
+impl<W: Register> fmt::Debug for Notified<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Notified")
+            .field("generation", &self.generation)
+            .field("done", &self.done)
+            .finish()
+    }
+}
@@ -0,0 +1,69 @@
+//! Strategies for bounded waker storage that runs out of room.
+
+/// What a bounded container does when asked to store a waker while it has no free slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowAction {
+    /// Push out the oldest stored waker, waking it so it gets a chance to pend again.
+    WakeEvicted,
+    /// Wake and clear everything that is stored, then store the new waker.
+    WakeAll,
+    /// Refuse to store the new waker.
+    Reject,
+}
+
+pub trait OverflowPolicy {
+    fn on_overflow() -> OverflowAction;
+}
+
+/// Evicts and wakes the oldest waker. This is the default policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WakeEvicted;
+
+impl OverflowPolicy for WakeEvicted {
+    #[inline]
+    fn on_overflow() -> OverflowAction {
+        OverflowAction::WakeEvicted
+    }
+}
+
+/// Wakes every stored waker to make room.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WakeAll;
+
+impl OverflowPolicy for WakeAll {
+    #[inline]
+    fn on_overflow() -> OverflowAction {
+        OverflowAction::WakeAll
+    }
+}
+
+/// Refuses new wakers once full.
+///
+/// A rejected waker is reported as [`PendOutcome::Full`](crate::PendOutcome::Full), which
+/// [`pend`](crate::WakersMut::pend) handles by waking it immediately so its task is never left
+/// waiting on a registration that didn't happen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reject;
+
+impl OverflowPolicy for Reject {
+    #[inline]
+    fn on_overflow() -> OverflowAction {
+        OverflowAction::Reject
+    }
+}
+
+/// Panics on overflow in debug builds, for storage that is sized to never fill up.
+///
+/// Release builds fall back to [`WakeEvicted`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DebugPanic;
+
+impl OverflowPolicy for DebugPanic {
+    #[inline]
+    fn on_overflow() -> OverflowAction {
+        if cfg!(debug_assertions) {
+            panic!("waker storage overflowed")
+        }
+        OverflowAction::WakeEvicted
+    }
+}
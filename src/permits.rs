@@ -0,0 +1,61 @@
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Permits handed out one at a time, as by [`Notify::notify_one`](crate::Notify::notify_one).
+///
+/// A permit handed to a woken waiter is owed to it until it's polled, however many of those are
+/// outstanding. A permit released while nobody is waiting is stored instead, and at most one of those
+/// is kept.
+#[derive(Debug)]
+pub(crate) struct Permits {
+    owed: AtomicUsize,
+    stored: AtomicBool,
+}
+
+impl Permits {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            owed: AtomicUsize::new(0),
+            stored: AtomicBool::new(false),
+        }
+    }
+
+    /// Releases a permit to whichever waiter `wake_one` wakes, storing it if there was nobody to wake.
+    pub(crate) fn release<F: FnOnce() -> bool>(&self, wake_one: F) {
+        self.owed.fetch_add(1, Ordering::AcqRel);
+        // the permit may have been taken by a waiter that showed up in the meantime, in which case
+        // there's nothing left to store
+        if !wake_one() && self.take_owed() {
+            self.stored.store(true, Ordering::Release);
+        }
+    }
+
+    /// Takes a permit that was released to a woken waiter but never claimed, and releases it again.
+    pub(crate) fn forward<F: FnOnce() -> bool>(&self, wake_one: F) {
+        if self.take_owed() {
+            self.release(wake_one)
+        }
+    }
+
+    #[inline]
+    fn take_owed(&self) -> bool {
+        self.owed.fetch_update(Ordering::AcqRel, Ordering::Acquire, |owed| owed.checked_sub(1)).is_ok()
+    }
+
+    #[inline]
+    pub(crate) fn take(&self) -> bool {
+        self.take_owed() || self.stored.swap(false, Ordering::AcqRel)
+    }
+
+    #[inline]
+    pub(crate) fn is_available(&self) -> bool {
+        self.owed.load(Ordering::Acquire) != 0 || self.stored.load(Ordering::Acquire)
+    }
+
+    /// Forgets every permit, owed or stored.
+    #[inline]
+    pub(crate) fn clear(&self) {
+        self.owed.store(0, Ordering::Release);
+        self.stored.store(false, Ordering::Release);
+    }
+}
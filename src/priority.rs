@@ -0,0 +1,479 @@
+use core::task::Waker;
+use core::marker::PhantomData;
+use core::{fmt, iter, array};
+#[cfg(feature = "alloc")]
+use core::{mem, cmp};
+#[cfg(feature = "alloc")]
+use alloc::{collections::{BinaryHeap, BTreeMap}, vec::{self, Vec}};
+#[cfg(feature = "alloc")]
+use super::identity::IdentityIndex;
+use super::overflow::{OverflowPolicy, OverflowAction, WakeEvicted};
+use super::{WakersMut, PendOutcome, RegisterMut, NONE};
+
+/// Fixed-capacity waker storage that wakes the highest priority waiters first.
+///
+/// Waiters of equal priority are woken in the order they were pended. [`WakersMut::pend`] registers
+/// with priority `0`, the lowest. Once full, the [`OverflowPolicy`] `P` decides what happens; with
+/// [`WakeEvicted`] the waiter that would have been woken last makes way, which may be the new one.
+pub struct PriorityWakers<const N: usize, P = WakeEvicted> {
+    /// Occupied slots are packed at the front, sorted in the order they'll be woken.
+    entries: [Option<Entry>; N],
+    next_key: usize,
+    policy: PhantomData<P>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    priority: u8,
+    /// The [`RegisterMut`] key of this entry.
+    key: usize,
+    waker: Waker,
+}
+
+impl<const N: usize, P> PriorityWakers<N, P> {
+    const VACANT: Option<Entry> = None;
+
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: [Self::VACANT; N],
+            next_key: 0,
+            policy: PhantomData,
+        }
+    }
+
+    /// Iterates over the stored wakers, in the order they'll be woken.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Waker> {
+        self.entries.iter().flatten().map(|e| &e.waker)
+    }
+
+    /// Like [`iter`](PriorityWakers::iter), but along with each waker's priority.
+    #[inline]
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (u8, &Waker)> {
+        self.entries.iter().flatten().map(|e| (e.priority, &e.waker))
+    }
+
+    fn remove_at(&mut self, i: usize) -> Option<Entry> {
+        let entry = self.entries[i].take();
+        self.entries[i..].rotate_left(1);
+        entry
+    }
+
+    fn fresh_key(&mut self) -> usize {
+        let key = self.next_key;
+        self.next_key = key.wrapping_add(1);
+        key
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> PriorityWakers<N, P> {
+    pub fn pend_with_priority(&mut self, waker: &Waker, priority: u8) -> PendOutcome {
+        let (outcome, evicted) = self.pend_with_priority_evicting(waker, priority);
+        for w in evicted {
+            w.wake()
+        }
+        outcome
+    }
+
+    /// Like [`pend_with_priority`](PriorityWakers::pend_with_priority), but hands back whatever was
+    /// evicted to make room rather than waking it.
+    pub fn pend_with_priority_evicting(&mut self, waker: &Waker, priority: u8) -> (PendOutcome, <Self as WakersMut>::Batch) {
+        let mut existing = None;
+        if let Some(i) = self.entries.iter().flatten().position(|e| e.waker.will_wake(waker)) {
+            match &self.entries[i] {
+                Some(e) if e.priority == priority => return (PendOutcome::AlreadyRegistered, IntoIterator::into_iter([NONE; N]).flatten()),
+                // re-sort it under its new priority, which always fits in the slot it left
+                _ => existing = self.remove_at(i),
+            }
+        }
+
+        let key = match &existing {
+            Some(e) => e.key,
+            None => self.fresh_key(),
+        };
+        let (outcome, evicted) = self.insert(Entry {
+            priority,
+            key,
+            waker: waker.clone(),
+        });
+        match existing {
+            Some(_) => (PendOutcome::AlreadyRegistered, evicted),
+            None => (outcome, evicted),
+        }
+    }
+
+    /// Sorts `entry` into place, without checking whether its waker is already registered.
+    fn insert(&mut self, entry: Entry) -> (PendOutcome, <Self as WakersMut>::Batch) {
+        let mut evicted = [NONE; N];
+        let len = self.entries.iter().take_while(|e| e.is_some()).count();
+        let i = self.entries[..len].iter().flatten()
+            .position(|e| e.priority < entry.priority)
+            .unwrap_or(len);
+
+        if len < N {
+            self.entries[i..=len].rotate_right(1);
+            self.entries[i] = Some(entry);
+            return (PendOutcome::Inserted, IntoIterator::into_iter(evicted).flatten())
+        }
+
+        let outcome = match P::on_overflow() {
+            OverflowAction::WakeEvicted if i < N => {
+                evicted[0] = self.entries[N - 1].take().map(|e| e.waker);
+                self.entries[i..].rotate_right(1);
+                self.entries[i] = Some(entry);
+                PendOutcome::Evicted
+            },
+            OverflowAction::WakeAll if N > 0 => {
+                let all = self.take_all();
+                self.entries[0] = Some(entry);
+                return (PendOutcome::Evicted, all)
+            },
+            _ => PendOutcome::Full,
+        };
+
+        (outcome, IntoIterator::into_iter(evicted).flatten())
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> WakersMut for PriorityWakers<N, P> {
+    type Batch = iter::Flatten<array::IntoIter<Option<Waker>, N>>;
+
+    #[inline]
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        self.pend_with_priority_evicting(waker, 0)
+    }
+
+    #[inline]
+    fn take_all(&mut self) -> Self::Batch {
+        self.take_n(N)
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let n = n.min(N);
+        let mut batch = [NONE; N];
+        for (taken, entry) in batch.iter_mut().zip(&mut self.entries[..n]) {
+            *taken = entry.take().map(|e| e.waker);
+        }
+        self.entries.rotate_left(n);
+        IntoIterator::into_iter(batch).flatten()
+    }
+
+    fn clone_all(&self) -> Self::Batch {
+        let mut batch = [NONE; N];
+        for (cloned, entry) in batch.iter_mut().zip(&self.entries) {
+            *cloned = entry.as_ref().map(|e| e.waker.clone());
+        }
+        IntoIterator::into_iter(batch).flatten()
+    }
+
+    fn remove(&mut self, waker: &Waker) -> bool {
+        match self.entries.iter().flatten().position(|e| e.waker.will_wake(waker)) {
+            Some(i) => self.remove_at(i).is_some(),
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.entries.iter().take_while(|e| e.is_some()).count()
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+/// Keyed registrations are made with priority `0`.
+impl<const N: usize, P: OverflowPolicy> RegisterMut for PriorityWakers<N, P> {
+    type Key = usize;
+
+    fn register_key_evicting(&mut self, key: Option<usize>, waker: &Waker) -> (Option<usize>, Self::Batch) {
+        if let Some(e) = key.and_then(|key| self.entries.iter_mut().flatten().find(|e| e.key == key)) {
+            if !e.waker.will_wake(waker) {
+                e.waker = waker.clone();
+            }
+            return (key, IntoIterator::into_iter([NONE; N]).flatten())
+        }
+
+        let key = self.fresh_key();
+        let (outcome, evicted) = self.insert(Entry {
+            priority: 0,
+            key,
+            waker: waker.clone(),
+        });
+        (Some(key).filter(|_| outcome.is_registered()), evicted)
+    }
+
+    fn deregister_key(&mut self, key: usize) -> bool {
+        match self.entries.iter().flatten().position(|e| e.key == key) {
+            Some(i) => self.remove_at(i).is_some(),
+            None => false,
+        }
+    }
+}
+
+impl<const N: usize, P> Default for PriorityWakers<N, P> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, P> Clone for PriorityWakers<N, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            next_key: self.next_key,
+            policy: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, P> fmt::Debug for PriorityWakers<N, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PriorityWakers")
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl<const N: usize, P> const_default::ConstDefault for PriorityWakers<N, P> {
+    const DEFAULT: Self = Self::new();
+}
+
+/// Unbounded waker storage that wakes the highest priority waiters first, backed by a binary heap.
+///
+/// Waiters of equal priority are woken in the order they were pended. [`WakersMut::pend`] registers
+/// with priority `0`, the lowest. Wakers are indexed by identity, so pending a new waker takes
+/// O(log n) no matter how many are registered.
+///
+/// The wakers themselves live in a table keyed by sequence number, next to a heap that only orders
+/// those numbers. Re-registering a key, or withdrawing it, only touches the table; the heap entries
+/// it leaves behind are skipped once they reach the top.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct PriorityHeapWakers {
+    heap: BinaryHeap<HeapEntry>,
+    /// The priority and waker of every live entry, by sequence number.
+    wakers: BTreeMap<u64, (u8, Waker)>,
+    index: IdentityIndex,
+    next_seq: u64,
+}
+
+/// An entry in the heap, which is stale unless `wakers` still holds `seq` at this priority.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeapEntry {
+    priority: u8,
+    seq: u64,
+}
+
+#[cfg(feature = "alloc")]
+impl HeapEntry {
+    #[inline]
+    fn rank(&self) -> (u8, cmp::Reverse<u64>) {
+        (self.priority, cmp::Reverse(self.seq))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd for HeapEntry {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Ord for HeapEntry {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PriorityHeapWakers {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            wakers: BTreeMap::new(),
+            index: IdentityIndex::new(),
+            next_seq: 0,
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(capacity),
+            wakers: BTreeMap::new(),
+            index: IdentityIndex::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Iterates over the stored wakers, in no particular order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Waker> {
+        self.wakers.values().map(|(_, waker)| waker)
+    }
+
+    /// Like [`iter`](PriorityHeapWakers::iter), but along with each waker's priority.
+    #[inline]
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (u8, &Waker)> {
+        self.wakers.values().map(|(priority, waker)| (*priority, waker))
+    }
+
+    pub fn pend_with_priority(&mut self, waker: &Waker, priority: u8) -> PendOutcome {
+        if !self.index.contains(waker) {
+            self.push(waker, priority);
+            return PendOutcome::Inserted
+        }
+
+        let existing = self.wakers.iter_mut().find(|(_, (_, w))| w.will_wake(waker));
+        if let Some((&seq, (p, _))) = existing {
+            if *p != priority {
+                // keep its sequence number, so it keeps its place among waiters of the new priority
+                *p = priority;
+                self.heap.push(HeapEntry { priority, seq });
+                self.compact();
+            }
+        }
+        PendOutcome::AlreadyRegistered
+    }
+
+    fn push(&mut self, waker: &Waker, priority: u8) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let waker = waker.clone();
+        self.index.insert(&waker);
+        self.wakers.insert(seq, (priority, waker));
+        self.heap.push(HeapEntry { priority, seq });
+        seq
+    }
+
+    #[inline]
+    fn is_live(&self, entry: &HeapEntry) -> bool {
+        self.wakers.get(&entry.seq).is_some_and(|&(priority, _)| priority == entry.priority)
+    }
+
+    fn remove_seq(&mut self, seq: u64) -> Option<Waker> {
+        let (_, waker) = self.wakers.remove(&seq)?;
+        self.index.remove(&waker);
+        self.compact();
+        Some(waker)
+    }
+
+    /// Drops stale heap entries once they outnumber the live ones.
+    fn compact(&mut self) {
+        if self.heap.len() > self.wakers.len() * 2 + 8 {
+            let wakers = &self.wakers;
+            self.heap.retain(|e| wakers.get(&e.seq).is_some_and(|&(priority, _)| priority == e.priority));
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl WakersMut for PriorityHeapWakers {
+    type Batch = vec::IntoIter<Waker>;
+
+    /// The heap grows as needed, so nothing is ever evicted.
+    #[inline]
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        (self.pend_with_priority(waker, 0), Vec::new().into_iter())
+    }
+
+    fn take_all(&mut self) -> Self::Batch {
+        let mut entries = mem::take(&mut self.heap).into_vec();
+        entries.retain(|e| self.is_live(e));
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut wakers = mem::take(&mut self.wakers);
+        self.index.clear();
+        entries.into_iter()
+            .filter_map(|e| wakers.remove(&e.seq).map(|(_, w)| w))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let mut batch = Vec::with_capacity(n.min(self.wakers.len()));
+        while batch.len() < n {
+            let entry = match self.heap.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if self.is_live(&entry) {
+                if let Some((_, waker)) = self.wakers.remove(&entry.seq) {
+                    self.index.remove(&waker);
+                    batch.push(waker);
+                }
+            }
+        }
+        batch.into_iter()
+    }
+
+    fn clone_all(&self) -> Self::Batch {
+        let mut entries: Vec<_> = self.wakers.iter().collect();
+        entries.sort_unstable_by_key(|&(&seq, &(priority, _))| cmp::Reverse(HeapEntry { priority, seq }));
+        entries.into_iter().map(|(_, (_, w))| w.clone()).collect::<Vec<_>>().into_iter()
+    }
+
+    fn remove(&mut self, waker: &Waker) -> bool {
+        if !self.index.contains(waker) {
+            return false
+        }
+
+        match self.wakers.iter().find(|(_, (_, w))| w.will_wake(waker)) {
+            Some((&seq, _)) => self.remove_seq(seq).is_some(),
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.wakers.len()
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Keyed registrations are made with priority `0`.
+#[cfg(feature = "alloc")]
+impl RegisterMut for PriorityHeapWakers {
+    type Key = u64;
+
+    fn register_key_evicting(&mut self, key: Option<u64>, waker: &Waker) -> (Option<u64>, Self::Batch) {
+        if let Some(key) = key {
+            if let Some((_, w)) = self.wakers.get_mut(&key) {
+                if !w.will_wake(waker) {
+                    self.index.remove(w);
+                    *w = waker.clone();
+                    self.index.insert(w);
+                }
+                return (Some(key), Vec::new().into_iter())
+            }
+        }
+
+        (Some(self.push(waker, 0)), Vec::new().into_iter())
+    }
+
+    #[inline]
+    fn deregister_key(&mut self, key: u64) -> bool {
+        self.remove_seq(key).is_some()
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "const-default"))]
+impl const_default::ConstDefault for PriorityHeapWakers {
+    const DEFAULT: Self = Self::new();
+}
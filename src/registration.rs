@@ -0,0 +1,68 @@
+use core::task::Waker;
+use core::fmt;
+use super::Register;
+
+/// Keeps a waker registered with a container until dropped.
+///
+/// Meant to live inside a future: [`pend`](Registration::pend) each time it's polled, and if the
+/// future is dropped before being woken its registration is withdrawn, rather than lingering to cause
+/// a spurious wakeup (or pushing a live waiter out of bounded storage).
+pub struct Registration<'a, W: Register + ?Sized> {
+    wakers: &'a W,
+    key: Option<W::Key>,
+}
+
+impl<'a, W: Register + ?Sized> Registration<'a, W> {
+    /// Creates a guard that hasn't registered anything yet.
+    #[inline]
+    pub fn new(wakers: &'a W) -> Self {
+        Self {
+            wakers,
+            key: None,
+        }
+    }
+
+    /// Registers `waker`, reusing the existing entry if it hasn't been woken since the last call.
+    ///
+    /// A waker that couldn't be stored is woken immediately, and `false` is returned.
+    pub fn pend(&mut self, waker: &Waker) -> bool {
+        self.key = self.wakers.register_key_by_ref(self.key.take(), waker);
+        match self.key {
+            Some(_) => true,
+            None => {
+                waker.wake_by_ref();
+                false
+            },
+        }
+    }
+
+    /// Withdraws the registration without waking it, returning whether it was still registered.
+    ///
+    /// `false` means the registration was woken (or was never made).
+    pub fn deregister(&mut self) -> bool {
+        match self.key.take() {
+            Some(key) => self.wakers.deregister_key_by_ref(key),
+            None => false,
+        }
+    }
+
+    #[inline]
+    pub fn wakers(&self) -> &'a W {
+        self.wakers
+    }
+}
+
+impl<W: Register + ?Sized> Drop for Registration<'_, W> {
+    #[inline]
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
+
+impl<W: Register + ?Sized> fmt::Debug for Registration<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Registration")
+            .field("pending", &self.key.is_some())
+            .finish()
+    }
+}
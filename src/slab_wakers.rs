@@ -0,0 +1,251 @@
+use core::task::Waker;
+use core::mem;
+use alloc::collections::VecDeque;
+use slab::Slab;
+use super::identity::IdentityIndex;
+use super::{WakersMut, PendOutcome, RegisterMut};
+
+/// Unbounded waker storage backed by a [`Slab`].
+///
+/// Callers that [`register`](SlabWakers::register) a waker are handed a [`SlabKey`] that can later be
+/// used to [`update`](SlabWakers::update) or [`deregister`](SlabWakers::deregister) that exact entry.
+/// Once the entry is woken its key goes stale, and never touches whichever entry reuses its slot.
+///
+/// [`pend`](WakersMut::pend) finds duplicates through an identity index (`IdentityIndex`, in
+/// `identity.rs`) instead of scanning the slab.
+#[derive(Debug, Clone, Default)]
+pub struct SlabWakers {
+    wakers: Slab<Entry>,
+    index: IdentityIndex,
+    /// Every key in the order it was registered, so [`take_n`](WakersMut::take_n) can find the
+    /// longest-waiting entries without scanning. Keys are left behind when their entry is removed,
+    /// and skipped once they reach the front.
+    order: VecDeque<SlabKey>,
+    /// Stamped onto each entry, so a stale key can be told apart from one that reused its slot.
+    next_seq: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    seq: usize,
+    waker: Waker,
+}
+
+/// Addresses one entry of a [`SlabWakers`].
+///
+/// Pairs the slab index with the entry's sequence number, so that a stale key can't touch an
+/// unrelated entry that has since reused its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SlabKey {
+    index: usize,
+    seq: usize,
+}
+
+impl SlabWakers {
+    pub const fn new() -> Self {
+        Self {
+            wakers: Slab::new(),
+            index: IdentityIndex::new(),
+            order: VecDeque::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            wakers: Slab::with_capacity(capacity),
+            index: IdentityIndex::new(),
+            order: VecDeque::with_capacity(capacity),
+            next_seq: 0,
+        }
+    }
+
+    /// Stores `waker` in a new entry, returning its key.
+    pub fn register(&mut self, waker: &Waker) -> SlabKey {
+        let seq = self.next_seq;
+        self.next_seq = seq.wrapping_add(1);
+        let waker = waker.clone();
+        self.index.insert(&waker);
+        let index = self.wakers.insert(Entry {
+            seq,
+            waker,
+        });
+        let key = SlabKey {
+            index,
+            seq,
+        };
+        self.order.push_back(key);
+        key
+    }
+
+    fn entry(&self, key: SlabKey) -> Option<&Entry> {
+        self.wakers.get(key.index).filter(|entry| entry.seq == key.seq)
+    }
+
+    /// Drops the keys of removed entries once they outnumber the live ones, so that registering and
+    /// deregistering without ever waking doesn't grow `order` without bound.
+    fn compact(&mut self) {
+        if self.order.len() > self.wakers.len() * 2 + 8 {
+            let wakers = &self.wakers;
+            self.order.retain(|key| wakers.get(key.index).is_some_and(|entry| entry.seq == key.seq));
+        }
+    }
+
+    /// Replaces the waker stored under `key`.
+    ///
+    /// Returns `false` if the entry is no longer registered (it may have been woken), in which case
+    /// the caller needs to [`register`](SlabWakers::register) again.
+    pub fn update(&mut self, key: SlabKey, waker: &Waker) -> bool {
+        match self.wakers.get_mut(key.index).filter(|entry| entry.seq == key.seq) {
+            Some(entry) => {
+                if !entry.waker.will_wake(waker) {
+                    self.index.remove(&entry.waker);
+                    entry.waker = waker.clone();
+                    self.index.insert(&entry.waker);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Removes the entry under `key` without waking it, returning its waker if it was still registered.
+    pub fn deregister(&mut self, key: SlabKey) -> Option<Waker> {
+        self.entry(key)?;
+        let waker = self.wakers.remove(key.index).waker;
+        self.index.remove(&waker);
+        self.compact();
+        Some(waker)
+    }
+
+    /// Iterates over the stored wakers, in slab order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Waker> {
+        self.wakers.iter().map(|(_, entry)| &entry.waker)
+    }
+
+    /// Like [`iter`](SlabWakers::iter), but along with the key of each waker's entry.
+    #[inline]
+    pub fn iter_keyed(&self) -> impl Iterator<Item = (SlabKey, &Waker)> {
+        self.wakers.iter().map(|(index, entry)| (SlabKey { index, seq: entry.seq }, &entry.waker))
+    }
+}
+
+impl WakersMut for SlabWakers {
+    type Batch = SlabBatch;
+
+    /// The slab grows as needed, so nothing is ever evicted.
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        let outcome = if self.index.contains(waker) {
+            PendOutcome::AlreadyRegistered
+        } else {
+            self.register(waker);
+            PendOutcome::Inserted
+        };
+        (outcome, SlabBatch::empty())
+    }
+
+    /// Wakers are taken in key order, rather than the order they were registered in.
+    fn take_all(&mut self) -> Self::Batch {
+        self.index.clear();
+        self.order.clear();
+        SlabBatch {
+            entries: mem::take(&mut self.wakers).into_iter(),
+        }
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let mut batch = Slab::with_capacity(n.min(self.wakers.len()));
+        while batch.len() < n {
+            let key = match self.order.pop_front() {
+                Some(key) => key,
+                None => break,
+            };
+            if self.entry(key).is_some() {
+                let entry = self.wakers.remove(key.index);
+                self.index.remove(&entry.waker);
+                batch.insert(entry);
+            }
+        }
+        SlabBatch {
+            entries: batch.into_iter(),
+        }
+    }
+
+    #[inline]
+    fn clone_all(&self) -> Self::Batch {
+        SlabBatch {
+            entries: self.wakers.clone().into_iter(),
+        }
+    }
+
+    fn remove(&mut self, waker: &Waker) -> bool {
+        if !self.index.contains(waker) {
+            return false
+        }
+
+        let found = self.iter_keyed().find(|(_, w)| w.will_wake(waker)).map(|(key, _)| key);
+        found.and_then(|key| self.deregister(key)).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.wakers.len()
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl RegisterMut for SlabWakers {
+    type Key = SlabKey;
+
+    fn register_key_evicting(&mut self, key: Option<SlabKey>, waker: &Waker) -> (Option<SlabKey>, Self::Batch) {
+        let key = match key {
+            Some(key) if self.update(key, waker) => key,
+            _ => self.register(waker),
+        };
+        (Some(key), SlabBatch::empty())
+    }
+
+    #[inline]
+    fn deregister_key(&mut self, key: SlabKey) -> bool {
+        self.deregister(key).is_some()
+    }
+}
+
+/// Wakers taken out of a [`SlabWakers`].
+#[derive(Debug)]
+pub struct SlabBatch {
+    entries: slab::IntoIter<Entry>,
+}
+
+impl SlabBatch {
+    #[inline]
+    fn empty() -> Self {
+        Self {
+            entries: Slab::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for SlabBatch {
+    type Item = Waker;
+
+    #[inline]
+    fn next(&mut self) -> Option<Waker> {
+        self.entries.next().map(|(_, entry)| entry.waker)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl const_default::ConstDefault for SlabWakers {
+    const DEFAULT: Self = Self::new();
+}
@@ -0,0 +1,200 @@
+use core::task::Waker;
+use core::{mem, iter};
+use alloc::collections::vec_deque::{self, VecDeque};
+use super::identity::IdentityIndex;
+use super::{WakersMut, PendOutcome, RegisterMut};
+
+/// Unbounded waker storage that grows as needed, waking in the order wakers were pended.
+///
+/// Backed by a ring buffer, so waking the longest-waiting wakers only touches the ones it wakes.
+///
+/// Like `SlabWakers`, [`pend`](WakersMut::pend) checks for duplicates through an identity index
+/// (`IdentityIndex`, in `identity.rs`) rather than comparing against every stored waker.
+#[derive(Debug, Clone, Default)]
+pub struct VecWakers {
+    /// Withdrawn registrations leave a `None` behind rather than shifting everything after them.
+    wakers: VecDeque<Option<Waker>>,
+    /// The [`RegisterMut`] key of each entry in `wakers`. Keys only ever increase, so re-registering
+    /// finds its entry by binary search.
+    keys: VecDeque<u64>,
+    len: usize,
+    index: IdentityIndex,
+    next_key: u64,
+}
+
+impl VecWakers {
+    pub const fn new() -> Self {
+        Self {
+            wakers: VecDeque::new(),
+            keys: VecDeque::new(),
+            len: 0,
+            index: IdentityIndex::new(),
+            next_key: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            wakers: VecDeque::with_capacity(capacity),
+            keys: VecDeque::with_capacity(capacity),
+            len: 0,
+            index: IdentityIndex::new(),
+            next_key: 0,
+        }
+    }
+
+    /// Iterates over the stored wakers, in the order they'll be woken.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Waker> {
+        self.wakers.iter().flatten()
+    }
+
+    fn push(&mut self, waker: &Waker) -> u64 {
+        let key = self.next_key;
+        self.next_key += 1;
+        let waker = waker.clone();
+        self.index.insert(&waker);
+        self.wakers.push_back(Some(waker));
+        self.keys.push_back(key);
+        self.len += 1;
+        key
+    }
+
+    /// The position of the live entry under `key`.
+    fn position(&self, key: u64) -> Option<usize> {
+        self.keys.binary_search(&key).ok().filter(|&i| self.wakers[i].is_some())
+    }
+
+    fn remove_at(&mut self, i: usize) {
+        if let Some(w) = self.wakers[i].take() {
+            self.index.remove(&w);
+            self.len -= 1;
+        }
+
+        while let Some(None) = self.wakers.front() {
+            self.wakers.pop_front();
+            self.keys.pop_front();
+        }
+        while let Some(None) = self.wakers.back() {
+            self.wakers.pop_back();
+            self.keys.pop_back();
+        }
+        self.compact();
+    }
+
+    /// Drops the entries left behind by withdrawn registrations once they outnumber the live ones.
+    fn compact(&mut self) {
+        if self.wakers.len() > self.len * 2 + 8 {
+            let wakers = &self.wakers;
+            let mut i = 0;
+            self.keys.retain(|_| {
+                i += 1;
+                wakers[i - 1].is_some()
+            });
+            self.wakers.retain(Option::is_some);
+        }
+    }
+}
+
+impl WakersMut for VecWakers {
+    type Batch = iter::Flatten<vec_deque::IntoIter<Option<Waker>>>;
+
+    /// The vector grows as needed, so nothing is ever evicted.
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        let outcome = if self.index.contains(waker) {
+            PendOutcome::AlreadyRegistered
+        } else {
+            self.push(waker);
+            PendOutcome::Inserted
+        };
+        (outcome, VecDeque::new().into_iter().flatten())
+    }
+
+    fn take_all(&mut self) -> Self::Batch {
+        self.keys.clear();
+        self.index.clear();
+        self.len = 0;
+        mem::take(&mut self.wakers).into_iter().flatten()
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let mut taken = 0;
+        let end = self.wakers.iter()
+            .position(|w| {
+                taken += w.is_some() as usize;
+                taken > n
+            })
+            .unwrap_or(self.wakers.len());
+
+        self.keys.drain(..end);
+        let batch = self.wakers.drain(..end).collect::<VecDeque<_>>();
+        for w in batch.iter().flatten() {
+            self.index.remove(w);
+            self.len -= 1;
+        }
+        batch.into_iter().flatten()
+    }
+
+    #[inline]
+    fn clone_all(&self) -> Self::Batch {
+        self.wakers.clone().into_iter().flatten()
+    }
+
+    fn remove(&mut self, waker: &Waker) -> bool {
+        if !self.index.contains(waker) {
+            return false
+        }
+
+        match self.wakers.iter().position(|w| w.as_ref().is_some_and(|w| w.will_wake(waker))) {
+            Some(i) => {
+                self.remove_at(i);
+                true
+            },
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl RegisterMut for VecWakers {
+    type Key = u64;
+
+    fn register_key_evicting(&mut self, key: Option<u64>, waker: &Waker) -> (Option<u64>, Self::Batch) {
+        if let Some(i) = key.and_then(|key| self.position(key)) {
+            if let Some(w) = &mut self.wakers[i] {
+                if !w.will_wake(waker) {
+                    self.index.remove(w);
+                    *w = waker.clone();
+                    self.index.insert(w);
+                }
+            }
+            return (key, VecDeque::new().into_iter().flatten())
+        }
+
+        (Some(self.push(waker)), VecDeque::new().into_iter().flatten())
+    }
+
+    fn deregister_key(&mut self, key: u64) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                self.remove_at(i);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "const-default")]
+impl const_default::ConstDefault for VecWakers {
+    const DEFAULT: Self = Self::new();
+}
@@ -0,0 +1,468 @@
+use core::task::Waker;
+use core::cell::Cell;
+use core::marker::{PhantomData, PhantomPinned};
+use core::ptr::NonNull;
+use core::pin::Pin;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::{self, Vec}};
+use super::WakersRef;
+#[cfg(feature = "alloc")]
+use super::{Wakers, WakersMut, PendOutcome};
+
+/// Guards every access to a [`WaiterList`]'s links.
+pub trait ListGuard {
+    fn with<R, F: FnOnce() -> R>(f: F) -> R;
+}
+
+/// Leaves a [`WaiterList`] unguarded, which keeps it `!Sync`: it can only be shared within a thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unguarded;
+
+impl ListGuard for Unguarded {
+    #[inline]
+    fn with<R, F: FnOnce() -> R>(f: F) -> R {
+        f()
+    }
+}
+
+/// Guards a [`WaiterList`] with a critical section, making it `Sync`, and its [`Waiter`]s `Send`.
+#[cfg(feature = "critical-section")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsGuard;
+
+#[cfg(feature = "critical-section")]
+impl ListGuard for CsGuard {
+    #[inline]
+    fn with<R, F: FnOnce() -> R>(f: F) -> R {
+        critical_section::with(|_| f())
+    }
+}
+
+/// An intrusive FIFO of waiters.
+///
+/// The list itself only holds head and tail pointers: each waiting future embeds a pinned [`Waiter`]
+/// node that holds its waker, and unlinks itself in O(1) when dropped, so waiting never allocates.
+/// Waiters are woken through [`WakersRef`] in the order they joined.
+///
+/// Under the `alloc` feature the list also implements [`Wakers`](crate::Wakers) and
+/// [`WakersMut`](crate::WakersMut), allocating a node for each bare waker pended that way. Those
+/// nodes are only ever deduplicated against and removed among themselves, never a `Waiter`'s, and
+/// finding them walks the list in O(n), as does [`len`](WaiterList::len).
+///
+/// By default the list is [`Unguarded`] and so can't be shared between threads. Guarding it with
+/// `CsGuard` (under the `critical-section` feature) lifts that restriction. Wakers are always
+/// woken and dropped outside of the guard.
+pub struct WaiterList<G: ListGuard = Unguarded> {
+    head: Cell<Option<NonNull<Node>>>,
+    tail: Cell<Option<NonNull<Node>>>,
+    /// Bumped by each wake, so a waiter that rejoins while being woken isn't woken again by that same call.
+    epoch: Cell<usize>,
+    guard: PhantomData<G>,
+}
+
+/// A `Waiter`'s node can only be linked while the `Waiter` borrows the list, so the only nodes linked
+/// while the list could be moved to another thread are the ones it allocated and owns outright.
+unsafe impl<G: ListGuard + Send> Send for WaiterList<G> { }
+
+/// Every access to the links happens within a critical section.
+#[cfg(feature = "critical-section")]
+unsafe impl Sync for WaiterList<CsGuard> { }
+
+struct Node {
+    waker: Cell<Option<Waker>>,
+    prev: Cell<Option<NonNull<Node>>>,
+    next: Cell<Option<NonNull<Node>>>,
+    linked: Cell<bool>,
+    /// The list's epoch at the time this node was linked.
+    epoch: Cell<usize>,
+    /// Whether the list allocated this node itself, for a waker pended through `Wakers`.
+    #[cfg(feature = "alloc")]
+    owned: bool,
+}
+
+impl Node {
+    const fn new() -> Self {
+        Self {
+            waker: Cell::new(None),
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            linked: Cell::new(false),
+            epoch: Cell::new(0),
+            #[cfg(feature = "alloc")]
+            owned: false,
+        }
+    }
+
+    /// Frees `node` if the list allocated it. It must have been unlinked.
+    #[inline]
+    unsafe fn release(node: NonNull<Node>) {
+        #[cfg(feature = "alloc")]
+        if unsafe { node.as_ref() }.owned {
+            drop(unsafe { Box::from_raw(node.as_ptr()) })
+        }
+        #[cfg(not(feature = "alloc"))]
+        let _ = node;
+    }
+}
+
+impl WaiterList {
+    #[inline]
+    pub const fn new() -> Self {
+        Self::guarded()
+    }
+}
+
+impl<G: ListGuard> WaiterList<G> {
+    /// Creates a list guarded by `G`, say `WaiterList::<CsGuard>::guarded()`.
+    #[inline]
+    pub const fn guarded() -> Self {
+        Self {
+            head: Cell::new(None),
+            tail: Cell::new(None),
+            epoch: Cell::new(0),
+            guard: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        G::with(|| self.head.get().is_none())
+    }
+
+    /// Counts the waiters in the list, in O(n).
+    pub fn len(&self) -> usize {
+        G::with(|| {
+            let mut len = 0;
+            let mut next = self.head.get();
+            while let Some(node) = next {
+                len += 1;
+                next = unsafe { node.as_ref() }.next.get();
+            }
+            len
+        })
+    }
+
+    /// Must be called within the guard.
+    fn link(&self, ptr: NonNull<Node>) {
+        let node = unsafe { ptr.as_ref() };
+        node.prev.set(self.tail.get());
+        node.next.set(None);
+        node.linked.set(true);
+        node.epoch.set(self.epoch.get());
+        match self.tail.get() {
+            // Safety: linked nodes are pinned and unlink themselves before being dropped
+            Some(tail) => unsafe { tail.as_ref() }.next.set(Some(ptr)),
+            None => self.head.set(Some(ptr)),
+        }
+        self.tail.set(Some(ptr));
+    }
+
+    /// Must be called within the guard.
+    fn unlink(&self, node: &Node) {
+        let (prev, next) = (node.prev.take(), node.next.take());
+        match prev {
+            Some(prev) => unsafe { prev.as_ref() }.next.set(next),
+            None => self.head.set(next),
+        }
+        match next {
+            Some(next) => unsafe { next.as_ref() }.prev.set(prev),
+            None => self.tail.set(prev),
+        }
+        node.linked.set(false);
+    }
+
+    /// Bumps the epoch, returning the one that the wake it's called for should stop at.
+    fn advance(&self) -> usize {
+        G::with(|| {
+            let epoch = self.epoch.get();
+            self.epoch.set(epoch.wrapping_add(1));
+            epoch
+        })
+    }
+
+    /// Unlinks the head of the list and takes its waker, unless it joined after `epoch`.
+    fn pop(&self, epoch: usize) -> Option<Option<Waker>> {
+        G::with(|| {
+            let head = self.head.get()?;
+            let node = unsafe { head.as_ref() };
+            if node.epoch.get().wrapping_sub(epoch) as isize > 0 {
+                return None
+            }
+            self.unlink(node);
+            let waker = node.waker.take();
+            unsafe { Node::release(head) };
+            Some(waker)
+        })
+    }
+
+    /// Moves the head of the list to its tail, returning its waker, unless it joined after `epoch`.
+    fn rotate(&self, epoch: usize) -> Option<Option<Waker>> {
+        G::with(|| {
+            let head = self.head.get()?;
+            let node = unsafe { head.as_ref() };
+            if node.epoch.get().wrapping_sub(epoch) as isize > 0 {
+                return None
+            }
+            self.unlink(node);
+            self.link(head);
+            let waker = node.waker.take();
+            node.waker.set(waker.clone());
+            Some(waker)
+        })
+    }
+}
+
+impl<G: ListGuard> WakersRef for WaiterList<G> {
+    #[inline]
+    fn wake_by_ref(&self) {
+        self.wake_n_by_ref(usize::MAX);
+    }
+
+    fn wake_n_by_ref(&self, n: usize) -> usize {
+        let epoch = self.advance();
+
+        let mut woken = 0;
+        while woken < n {
+            // the node is no longer referenced by the time it's woken, so the waker is free to drop it
+            match self.pop(epoch) {
+                Some(waker) => {
+                    if let Some(w) = waker {
+                        w.wake()
+                    }
+                    woken += 1;
+                },
+                None => break,
+            }
+        }
+        woken
+    }
+
+    fn wake_and_retain_by_ref(&self) {
+        let epoch = self.advance();
+
+        // each waiter rejoins the back of the list under the new epoch, so this stops once it comes
+        // back around to them
+        while let Some(waker) = self.rotate(epoch) {
+            if let Some(w) = waker {
+                w.wake()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<G: ListGuard> WaiterList<G> {
+    /// Finds the node the list allocated for a waker that will wake `waker`. Must be called within
+    /// the guard.
+    fn find_owned(&self, waker: &Waker) -> Option<NonNull<Node>> {
+        let mut next = self.head.get();
+        while let Some(ptr) = next {
+            let node = unsafe { ptr.as_ref() };
+            if node.owned {
+                let w = node.waker.take();
+                let found = w.as_ref().is_some_and(|w| w.will_wake(waker));
+                node.waker.set(w);
+                if found {
+                    return Some(ptr)
+                }
+            }
+            next = node.next.get();
+        }
+        None
+    }
+}
+
+/// Bare wakers get a node allocated for them, which is freed once they're woken or removed.
+#[cfg(feature = "alloc")]
+impl<G: ListGuard> Wakers for WaiterList<G> {
+    fn try_pend_by_ref(&self, waker: &Waker) -> PendOutcome {
+        G::with(|| {
+            if self.find_owned(waker).is_some() {
+                return PendOutcome::AlreadyRegistered
+            }
+            let node = Box::leak(Box::new(Node { owned: true, ..Node::new() }));
+            node.waker.set(Some(waker.clone()));
+            self.link(NonNull::from(node));
+            PendOutcome::Inserted
+        })
+    }
+
+    fn remove_by_ref(&self, waker: &Waker) -> bool {
+        let waker = G::with(|| {
+            let ptr = self.find_owned(waker)?;
+            let node = unsafe { ptr.as_ref() };
+            self.unlink(node);
+            let waker = node.waker.take();
+            unsafe { Node::release(ptr) };
+            waker
+        });
+        waker.is_some()
+    }
+}
+
+/// Borrowing the list mutably rules out any `Waiter`s, so only nodes the list allocated are left.
+#[cfg(feature = "alloc")]
+impl<G: ListGuard> WakersMut for WaiterList<G> {
+    type Batch = vec::IntoIter<Waker>;
+
+    #[inline]
+    fn try_pend_evicting(&mut self, waker: &Waker) -> (PendOutcome, Self::Batch) {
+        (self.try_pend_by_ref(waker), Vec::new().into_iter())
+    }
+
+    #[inline]
+    fn take_all(&mut self) -> Self::Batch {
+        self.take_n(usize::MAX)
+    }
+
+    fn take_n(&mut self, n: usize) -> Self::Batch {
+        let epoch = self.advance();
+        let mut batch = Vec::new();
+        while batch.len() < n {
+            match self.pop(epoch) {
+                Some(waker) => batch.extend(waker),
+                None => break,
+            }
+        }
+        batch.into_iter()
+    }
+
+    fn clone_all(&self) -> Self::Batch {
+        G::with(|| {
+            let mut batch = Vec::new();
+            let mut next = self.head.get();
+            while let Some(ptr) = next {
+                let node = unsafe { ptr.as_ref() };
+                let waker = node.waker.take();
+                batch.extend(waker.clone());
+                node.waker.set(waker);
+                next = node.next.get();
+            }
+            batch.into_iter()
+        })
+    }
+
+    #[inline]
+    fn remove(&mut self, waker: &Waker) -> bool {
+        self.remove_by_ref(waker)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        WaiterList::len(self)
+    }
+
+    #[inline]
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Frees whatever nodes the list allocated that are still waiting.
+impl<G: ListGuard> Drop for WaiterList<G> {
+    fn drop(&mut self) {
+        while let Some(head) = self.head.get() {
+            self.unlink(unsafe { head.as_ref() });
+            unsafe { Node::release(head) };
+        }
+    }
+}
+
+impl<G: ListGuard> Default for WaiterList<G> {
+    #[inline]
+    fn default() -> Self {
+        Self::guarded()
+    }
+}
+
+impl<G: ListGuard> fmt::Debug for WaiterList<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WaiterList")
+            .field("empty", &self.is_empty())
+            .finish()
+    }
+}
+
+/// A node in a [`WaiterList`], embedded in the future that's waiting.
+pub struct Waiter<'a, G: ListGuard = Unguarded> {
+    list: &'a WaiterList<G>,
+    node: Node,
+    _pin: PhantomPinned,
+}
+
+/// The node is only ever touched within a critical section, whichever thread it's on.
+#[cfg(feature = "critical-section")]
+unsafe impl Send for Waiter<'_, CsGuard> { }
+
+impl<'a, G: ListGuard> Waiter<'a, G> {
+    #[inline]
+    pub const fn new(list: &'a WaiterList<G>) -> Self {
+        Self {
+            list,
+            node: Node::new(),
+            _pin: PhantomPinned,
+        }
+    }
+
+    /// Joins the back of the list with `waker`, or just updates the waker if this node hasn't been
+    /// woken since it last joined.
+    pub fn pend(self: Pin<&mut Self>, waker: &Waker) {
+        let this = self.into_ref().get_ref();
+        let node = &this.node;
+        let replaced = G::with(|| {
+            let replaced = match node.waker.take() {
+                Some(w) if w.will_wake(waker) => {
+                    node.waker.set(Some(w));
+                    None
+                },
+                old => {
+                    node.waker.set(Some(waker.clone()));
+                    old
+                },
+            };
+            if !node.linked.get() {
+                this.list.link(NonNull::from(node));
+            }
+            replaced
+        });
+        drop(replaced);
+    }
+
+    /// Whether this node is waiting in the list, i.e. it has been pended and not yet woken.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        G::with(|| self.node.linked.get())
+    }
+
+    /// Leaves the list without being woken, returning whether this node was still waiting.
+    pub fn deregister(self: Pin<&mut Self>) -> bool {
+        let this = self.into_ref().get_ref();
+        let (waker, linked) = G::with(|| {
+            let linked = this.node.linked.get();
+            if linked {
+                this.list.unlink(&this.node);
+            }
+            (this.node.waker.take(), linked)
+        });
+        drop(waker);
+        linked
+    }
+}
+
+impl<G: ListGuard> Drop for Waiter<'_, G> {
+    #[inline]
+    fn drop(&mut self) {
+        G::with(|| if self.node.linked.get() {
+            self.list.unlink(&self.node);
+        })
+    }
+}
+
+impl<G: ListGuard> fmt::Debug for Waiter<'_, G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Waiter")
+            .field("pending", &self.is_pending())
+            .finish()
+    }
+}
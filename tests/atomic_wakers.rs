@@ -0,0 +1,94 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+use common::{counter, count};
+use wakers::{Wakers, WakersRef, Register, AtomicWakerSlot};
+
+/// Unparks a thread, remembering that it did so.
+struct Unpark {
+    thread: Thread,
+    woken: AtomicBool,
+}
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+/// Races registration against waking from another thread; a waker that's registered before the
+/// producer's wake must never be lost in the REGISTERING/WAKING handoff.
+#[test]
+fn concurrent_handoff() {
+    const ROUNDS: usize = 10_000;
+
+    let slot = Arc::new(AtomicWakerSlot::new());
+    let round = Arc::new(AtomicUsize::new(0));
+    let ack = Arc::new(AtomicUsize::new(0));
+
+    let producer = thread::spawn({
+        let (slot, round, ack) = (slot.clone(), round.clone(), ack.clone());
+        move || for i in 1..=ROUNDS {
+            while ack.load(Ordering::Acquire) != i - 1 {
+                std::hint::spin_loop();
+            }
+            round.store(i, Ordering::Release);
+            slot.wake_by_ref();
+        }
+    });
+
+    let unpark = Arc::new(Unpark {
+        thread: thread::current(),
+        woken: AtomicBool::new(false),
+    });
+    let waker = Waker::from(unpark.clone());
+    for i in 1..=ROUNDS {
+        loop {
+            unpark.woken.store(false, Ordering::Release);
+            slot.pend_by_ref(&waker);
+            if round.load(Ordering::Acquire) >= i {
+                break
+            }
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !unpark.woken.load(Ordering::Acquire) {
+                let now = Instant::now();
+                assert!(now < deadline, "lost wakeup in round {}", i);
+                thread::park_timeout(deadline - now);
+            }
+        }
+        ack.store(i, Ordering::Release);
+    }
+
+    producer.join().unwrap();
+}
+
+#[test]
+fn keyed_registration() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let slot = AtomicWakerSlot::new();
+
+    let key = slot.register_key_by_ref(None, &a).unwrap();
+    assert_eq!(slot.register_key_by_ref(Some(key), &b), Some(key), "a live key updates its entry");
+    assert_eq!(count(&a_count), 0);
+
+    // a new registration pushes the old one out, waking it
+    let other = slot.register_key_by_ref(None, &a).unwrap();
+    assert_ne!(other, key);
+    assert_eq!(count(&b_count), 1);
+    assert!(!slot.deregister_key_by_ref(key), "a stale key must not withdraw the new registration");
+    assert!(slot.deregister_key_by_ref(other));
+
+    slot.wake_by_ref();
+    assert_eq!(count(&a_count), 0);
+}
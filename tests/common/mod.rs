@@ -0,0 +1,69 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+/// A waker that counts how many times it's been woken.
+#[derive(Default)]
+pub struct Counter(AtomicUsize);
+
+impl Wake for Counter {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+pub fn counter() -> (Arc<Counter>, Waker) {
+    let counter = Arc::new(Counter::default());
+    (counter.clone(), counter.into())
+}
+
+pub fn count(counter: &Counter) -> usize {
+    counter.0.load(Ordering::SeqCst)
+}
+
+/// A waker that runs a closure whenever it's woken.
+pub struct FnWaker<F>(F);
+
+impl<F: Fn() + Send + Sync + 'static> Wake for FnWaker<F> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        (self.0)()
+    }
+}
+
+pub fn waker_fn<F: Fn() + Send + Sync + 'static>(f: F) -> Waker {
+    Arc::new(FnWaker(f)).into()
+}
+
+/// The order in which a set of [`logging`] wakers were woken.
+pub type WakeLog = Arc<Mutex<Vec<usize>>>;
+
+/// Creates `n` wakers that log their index when woken.
+pub fn logging(n: usize) -> (WakeLog, Vec<Waker>) {
+    let log = WakeLog::default();
+    let wakers = (0..n).map(|i| {
+        let log = log.clone();
+        waker_fn(move || log.lock().unwrap().push(i))
+    }).collect();
+    (log, wakers)
+}
+
+pub fn woken(log: &WakeLog) -> Vec<usize> {
+    log.lock().unwrap().clone()
+}
+
+pub fn poll<F: Future + Unpin>(future: &mut F, waker: &Waker) -> Poll<F::Output> {
+    Pin::new(future).poll(&mut Context::from_waker(waker))
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "critical-section")]
+
+mod common;
+
+use std::thread;
+use common::{counter, count, waker_fn};
+use wakers::{Wakers, WakersRef, CsWakers, CsLock, WakerQueue};
+
+/// Shared between threads as a plain `static`, the way firmware shares one with its interrupt
+/// handlers.
+#[test]
+fn static_between_threads() {
+    static WAKERS: CsWakers<WakerQueue<4>> = CsWakers::from_lock(CsLock::new(WakerQueue::new()));
+    const THREADS: usize = 4;
+
+    let counters: Vec<_> = (0..THREADS).map(|_| counter()).collect();
+    let threads: Vec<_> = counters.iter().map(|(_, waker)| thread::spawn({
+        let waker = waker.clone();
+        move || for _ in 0..100 {
+            WAKERS.pend_by_ref(&waker);
+            WAKERS.wake_by_ref();
+        }
+    })).collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    // with room for every thread, each registration is woken exactly once
+    assert!(WAKERS.inspect(|w| w.iter().next().is_none()));
+    for (c, _) in &counters {
+        assert!((1..=100).contains(&count(c)));
+    }
+}
+
+/// Re-registering from within a wake must not try to re-enter the critical section's borrow.
+#[test]
+fn static_repend_while_woken() {
+    static WAKERS: CsWakers<WakerQueue<2>> = CsWakers::from_lock(CsLock::new(WakerQueue::new()));
+
+    let (a_count, a) = counter();
+    let repend = waker_fn({
+        let a = a.clone();
+        move || {
+            WAKERS.pend_by_ref(&a);
+        }
+    });
+
+    WAKERS.pend_by_ref(&repend);
+    WAKERS.wake_by_ref();
+    WAKERS.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+}
+
+#[cfg(feature = "const-default")]
+#[test]
+fn const_default() {
+    use const_default::ConstDefault;
+
+    static WAKERS: CsWakers<WakerQueue<2>> = CsWakers::DEFAULT;
+    let (a_count, a) = counter();
+    WAKERS.pend_by_ref(&a);
+    WAKERS.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{counter, count, poll};
+use wakers::{Register, WakerQueue, SendWakers, AtomicWakerSlot, ByWaker, ManualResetEvent, AutoResetEvent};
+
+fn check_manual<W: Register>(event: &ManualResetEvent<W>) {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    let mut wait_a = event.wait();
+    let mut wait_b = event.wait();
+    assert!(poll(&mut wait_a, &a).is_pending());
+    assert!(poll(&mut wait_b, &b).is_pending());
+
+    event.set();
+    assert_eq!(count(&a_count) + count(&b_count), 2);
+    assert!(poll(&mut wait_a, &a).is_ready());
+    assert!(poll(&mut wait_b, &b).is_ready());
+    assert!(poll(&mut event.wait(), &a).is_ready(), "a set event releases every waiter");
+
+    event.reset();
+    let mut wait = event.wait();
+    assert!(poll(&mut wait, &a).is_pending());
+    drop(wait);
+    event.set();
+    assert_eq!(count(&a_count), 1, "a dropped waiter must withdraw its waker");
+}
+
+fn check_auto<W: Register>(event: &AutoResetEvent<W>) {
+    let (a_count, a) = counter();
+    let (_, b) = counter();
+
+    let mut wait_a = event.wait();
+    let mut wait_b = event.wait();
+    assert!(poll(&mut wait_a, &a).is_pending());
+    assert!(poll(&mut wait_b, &b).is_pending());
+
+    event.set();
+    assert_eq!(count(&a_count), 1);
+    assert!(poll(&mut wait_a, &a).is_ready());
+    assert!(poll(&mut wait_b, &b).is_pending(), "each set releases exactly one waiter");
+    assert!(!event.is_set());
+
+    // a woken waiter that drops out passes its release along
+    event.set();
+    drop(wait_b);
+    assert!(poll(&mut event.wait(), &a).is_ready());
+
+    event.set();
+    event.set();
+    assert!(poll(&mut event.wait(), &a).is_ready());
+    assert!(poll(&mut event.wait(), &a).is_pending(), "sets with nobody waiting don't accumulate");
+
+    // a release owed to a woken waiter doesn't stop the next one from being kept
+    let mut wait = event.wait();
+    assert!(poll(&mut wait, &a).is_pending());
+    event.set();
+    event.set();
+    assert!(event.is_set());
+    assert!(poll(&mut wait, &a).is_ready());
+    assert!(event.is_set(), "the second set must not be lost");
+    assert!(poll(&mut event.wait(), &a).is_ready());
+    assert!(!event.is_set());
+}
+
+/// Two waits polled by the same task each keep their own registration.
+fn check_same_task<W: Register>(manual: &ManualResetEvent<W>, auto: &AutoResetEvent<W>) {
+    let (counter, waker) = counter();
+
+    let mut first = manual.wait();
+    let mut second = manual.wait();
+    assert!(poll(&mut first, &waker).is_pending());
+    assert!(poll(&mut second, &waker).is_pending());
+    drop(first);
+    manual.set();
+    assert_eq!(count(&counter), 1, "dropping one wait must not withdraw the other");
+    assert!(poll(&mut second, &waker).is_ready());
+
+    let mut first = auto.wait();
+    let mut second = auto.wait();
+    assert!(poll(&mut first, &waker).is_pending());
+    assert!(poll(&mut second, &waker).is_pending());
+    drop(first);
+    auto.set();
+    assert_eq!(count(&counter), 2, "dropping one wait must not withdraw the other");
+    assert!(poll(&mut second, &waker).is_ready());
+}
+
+#[test]
+fn manual_reset() {
+    check_manual(&ManualResetEvent::new(SendWakers::new(WakerQueue::<4>::new())));
+}
+
+#[test]
+fn auto_reset() {
+    check_auto(&AutoResetEvent::new(SendWakers::new(WakerQueue::<4>::new())));
+}
+
+#[test]
+fn same_task() {
+    check_same_task(
+        &ManualResetEvent::new(SendWakers::new(WakerQueue::<4>::new())),
+        &AutoResetEvent::new(SendWakers::new(WakerQueue::<4>::new())),
+    );
+}
+
+#[test]
+fn by_waker() {
+    check_auto(&AutoResetEvent::new(ByWaker::new(SendWakers::new(WakerQueue::<4>::new()))));
+
+    // two waits in the same task share one entry, so dropping one wakes the task to restore it
+    let event = ManualResetEvent::new(ByWaker::new(SendWakers::new(WakerQueue::<4>::new())));
+    let (counter, waker) = counter();
+    let mut first = event.wait();
+    let mut second = event.wait();
+    assert!(poll(&mut first, &waker).is_pending());
+    assert!(poll(&mut second, &waker).is_pending());
+    drop(first);
+    assert_eq!(count(&counter), 1);
+    assert!(poll(&mut second, &waker).is_pending());
+    event.set();
+    assert_eq!(count(&counter), 2);
+    assert!(poll(&mut second, &waker).is_ready());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sync_events() {
+    use wakers::{SyncWakers, VecWakers};
+
+    check_manual(&ManualResetEvent::new(SyncWakers::new(VecWakers::new())));
+    check_auto(&AutoResetEvent::new(SyncWakers::new(VecWakers::new())));
+    check_same_task(
+        &ManualResetEvent::new(SyncWakers::new(VecWakers::new())),
+        &AutoResetEvent::new(SyncWakers::new(VecWakers::new())),
+    );
+}
+
+#[test]
+fn atomic_slot() {
+    let event = AutoResetEvent::new(AtomicWakerSlot::new());
+    let (counter, waker) = counter();
+
+    let mut wait = event.wait();
+    assert!(poll(&mut wait, &waker).is_pending());
+    event.set();
+    assert_eq!(count(&counter), 1);
+    assert!(poll(&mut wait, &waker).is_ready());
+
+    let mut wait = event.wait();
+    assert!(poll(&mut wait, &waker).is_pending());
+    drop(wait);
+    event.set();
+    assert_eq!(count(&counter), 1, "a dropped waiter must withdraw its waker");
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{counter, count};
+use wakers::{Wakers, AtomicWakerSlot, static_waker};
+
+#[cfg(feature = "alloc")]
+#[test]
+fn arc_balance() {
+    use std::sync::Arc;
+    use wakers::arc_waker;
+
+    let (a_count, a) = counter();
+    let slot = Arc::new(AtomicWakerSlot::new());
+
+    let waker = arc_waker(slot.clone());
+    assert_eq!(Arc::strong_count(&slot), 2);
+    let clone = waker.clone();
+    assert_eq!(Arc::strong_count(&slot), 3);
+
+    slot.pend_by_ref(&a);
+    clone.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+    assert_eq!(Arc::strong_count(&slot), 3, "waking by reference keeps the clone");
+
+    slot.pend_by_ref(&a);
+    clone.wake();
+    assert_eq!(count(&a_count), 2);
+    assert_eq!(Arc::strong_count(&slot), 2, "waking by value consumes the clone");
+
+    drop(waker);
+    assert_eq!(Arc::strong_count(&slot), 1);
+}
+
+#[test]
+fn static_container() {
+    static SLOT: AtomicWakerSlot = AtomicWakerSlot::new();
+    let (a_count, a) = counter();
+
+    let waker = static_waker(&SLOT);
+    let clone = waker.clone();
+
+    SLOT.pend_by_ref(&a);
+    waker.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+
+    SLOT.pend_by_ref(&a);
+    clone.wake();
+    assert_eq!(count(&a_count), 2);
+
+    waker.wake();
+    assert_eq!(count(&a_count), 2, "nothing was registered");
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{counter, count};
+use wakers::{Wakers, WakersRef, WakerQueue, SendWakers, GenerationWakers, EpochChanged, PendOutcome};
+
+#[test]
+fn refuses_after_wake() {
+    let (a_count, a) = counter();
+    let wakers = GenerationWakers::new(SendWakers::new(WakerQueue::<2>::new()));
+
+    let epoch = wakers.epoch();
+    wakers.wake_by_ref();
+    assert_eq!(wakers.pend_if_unchanged(epoch, &a), Err(EpochChanged { current: epoch.wrapping_add(1) }));
+    wakers.wake_by_ref();
+    assert_eq!(count(&a_count), 0, "a refused waker is not registered");
+
+    let epoch = wakers.epoch();
+    assert_eq!(wakers.pend_if_unchanged(epoch, &a), Ok(PendOutcome::Inserted));
+    assert_eq!(wakers.pend_if_unchanged(epoch, &a), Ok(PendOutcome::AlreadyRegistered));
+    wakers.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+}
+
+#[test]
+fn what_moves_the_epoch() {
+    let (_, a) = counter();
+    let wakers = GenerationWakers::new(SendWakers::new(WakerQueue::<2>::new()));
+
+    let epoch = wakers.epoch();
+    wakers.pend_by_ref(&a);
+    wakers.remove_by_ref(&a);
+    assert_eq!(wakers.epoch(), epoch, "registering and removing aren't wakes");
+
+    let epoch = wakers.epoch();
+    wakers.wake_one_by_ref();
+    assert!(wakers.pend_if_unchanged(epoch, &a).is_err());
+
+    let epoch = wakers.epoch();
+    wakers.wake_and_retain_by_ref();
+    assert!(wakers.pend_if_unchanged(epoch, &a).is_err(), "a retaining wake is still a wake");
+
+    let epoch = wakers.epoch();
+    wakers.wake_n_by_ref(0);
+    assert!(wakers.pend_if_unchanged(epoch, &a).is_err(), "even a wake of nobody counts");
+}
+
+/// A wake that lands while the waker is being registered is reported too, so the caller polls again
+/// rather than trusting a registration that may have just missed it.
+#[cfg(feature = "std")]
+#[test]
+fn refuses_racing_wake() {
+    use std::sync::Arc;
+    use common::waker_fn;
+    use wakers::SyncWakers;
+
+    let (a_count, a) = counter();
+    let wakers = Arc::new(GenerationWakers::new(SyncWakers::new(WakerQueue::<1>::new())));
+
+    // the waker evicted to make room for `a` wakes the container as it goes
+    let evicted = waker_fn({
+        let wakers = Arc::downgrade(&wakers);
+        move || if let Some(wakers) = wakers.upgrade() {
+            wakers.wake_and_retain_by_ref()
+        }
+    });
+    wakers.pend_by_ref(&evicted);
+
+    let epoch = wakers.epoch();
+    assert_eq!(wakers.pend_if_unchanged(epoch, &a), Err(EpochChanged { current: epoch.wrapping_add(1) }));
+    assert_eq!(count(&a_count), 1, "the retaining wake reached the waker it raced with");
+}
+
+#[test]
+fn display() {
+    assert_eq!(EpochChanged { current: 3 }.to_string(), "wakers were woken since the epoch was observed (now 3)");
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use common::{counter, count, waker_fn};
+use wakers::{WakerQueue, InterestWakers, Interest, PendOutcome};
+
+#[test]
+fn custom_classes() {
+    let custom = Interest::custom(3) | Interest::custom(7);
+    assert_eq!(custom.bits(), 0b1000_1000);
+    assert!(!custom.intersects(Interest::READABLE | Interest::WRITABLE | Interest::ERROR));
+}
+
+#[test]
+#[should_panic]
+fn custom_aliasing_builtin() {
+    Interest::custom(2);
+}
+
+#[test]
+#[should_panic]
+fn custom_out_of_range() {
+    Interest::custom(8);
+}
+
+#[test]
+fn wakes_only_ready_classes() {
+    let (r_count, r) = counter();
+    let (w_count, w) = counter();
+    let (rw_count, rw) = counter();
+    let mut wakers = InterestWakers::<WakerQueue<2>>::new();
+
+    assert_eq!(wakers.pend_interest(Interest::READABLE, &r), PendOutcome::Inserted);
+    assert_eq!(wakers.pend_interest(Interest::WRITABLE, &w), PendOutcome::Inserted);
+    assert_eq!(wakers.pend_interest(Interest::READABLE | Interest::WRITABLE, &rw), PendOutcome::Inserted);
+    assert_eq!(wakers.pend_interest(Interest::READABLE, &rw), PendOutcome::AlreadyRegistered);
+
+    wakers.wake_ready(Interest::READABLE);
+    assert_eq!((count(&r_count), count(&w_count), count(&rw_count)), (1, 0, 1));
+
+    wakers.wake_ready(Interest::WRITABLE | Interest::ERROR);
+    assert_eq!((count(&r_count), count(&w_count), count(&rw_count)), (1, 1, 2));
+}
+
+#[test]
+fn empty_interest() {
+    let (a_count, a) = counter();
+    let mut wakers = InterestWakers::<WakerQueue<2>>::new();
+
+    assert_eq!(wakers.pend_interest(Interest::default(), &a), PendOutcome::Full);
+    let (outcome, evicted) = wakers.pend_interest_evicting(Interest::from_bits(0), &a);
+    assert_eq!(outcome, PendOutcome::Full, "nothing was stored, so the caller must not wait");
+    assert_eq!(evicted.count(), 0);
+
+    wakers.wake_ready(Interest::from_bits(u8::MAX));
+    assert_eq!(count(&a_count), 0);
+}
+
+#[test]
+fn evicting() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let mut wakers = InterestWakers::<WakerQueue<1>>::new();
+
+    wakers.pend_interest(Interest::READABLE | Interest::WRITABLE, &a);
+    let (outcome, evicted) = wakers.pend_interest_evicting(Interest::READABLE, &b);
+    assert_eq!(outcome, PendOutcome::Evicted);
+    assert_eq!(count(&a_count), 0, "the evicted waker is handed back rather than woken");
+    evicted.for_each(Waker::wake);
+    assert_eq!(count(&a_count), 1);
+
+    wakers.wake_ready(Interest::READABLE | Interest::WRITABLE);
+    assert_eq!((count(&a_count), count(&b_count)), (2, 1));
+}
+
+/// Taking the ready waiters out lets the wake happen after the lock guarding them is released, so a
+/// waker that locks it again doesn't deadlock.
+#[test]
+fn wake_after_unlock() {
+    let wakers = Arc::new(Mutex::new(InterestWakers::<WakerQueue<2>>::new()));
+    let relock = waker_fn({
+        let wakers = wakers.clone();
+        move || assert!(wakers.try_lock().is_ok(), "woken under the lock")
+    });
+    let (other_count, other) = counter();
+
+    wakers.lock().unwrap().pend_interest(Interest::READABLE | Interest::custom(5), &relock);
+    wakers.lock().unwrap().pend_interest(Interest::WRITABLE, &other);
+    let taken: Vec<_> = wakers.lock().unwrap().take_ready(Interest::READABLE | Interest::custom(5)).collect();
+    assert_eq!(taken.len(), 2);
+    taken.into_iter().for_each(Waker::wake);
+    assert_eq!(count(&other_count), 0);
+
+    assert_eq!(wakers.lock().unwrap().take_ready(Interest::READABLE).count(), 0);
+    assert_eq!(wakers.lock().unwrap().take_ready(Interest::WRITABLE).count(), 1);
+}
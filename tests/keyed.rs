@@ -0,0 +1,91 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use common::{counter, count, waker_fn};
+use wakers::{WakersMut, WakerQueue, ArrayKeyedWakers, PendOutcome};
+
+#[test]
+fn array_wakes_only_its_key() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let mut keyed = ArrayKeyedWakers::<u8, WakerQueue<2>, 2>::new();
+
+    assert_eq!(keyed.pend_key(1, &a), PendOutcome::Inserted);
+    assert_eq!(keyed.pend_key(2, &b), PendOutcome::Inserted);
+    assert_eq!(keyed.pend_key(3, &a), PendOutcome::Full, "every key is in use");
+
+    keyed.wake_key(&1);
+    assert_eq!((count(&a_count), count(&b_count)), (1, 0));
+
+    // waking a key frees its place for another
+    assert_eq!(keyed.pend_key(3, &a), PendOutcome::Inserted);
+    keyed.wake_all();
+    assert_eq!((count(&a_count), count(&b_count)), (2, 1));
+    assert_eq!(keyed.take_all().count(), 0);
+}
+
+#[test]
+fn array_evicting() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let mut keyed = ArrayKeyedWakers::<u8, WakerQueue<1>, 1>::new();
+
+    keyed.pend_key(1, &a);
+    let (outcome, evicted) = keyed.pend_key_evicting(1, &b);
+    assert_eq!(outcome, PendOutcome::Evicted);
+    assert_eq!(count(&a_count), 0, "the evicted waker is handed back rather than woken");
+    evicted.for_each(Waker::wake);
+    assert_eq!(count(&a_count), 1);
+
+    let (outcome, evicted) = keyed.pend_key_evicting(2, &a);
+    assert_eq!(outcome, PendOutcome::Full);
+    assert_eq!(evicted.count(), 0);
+
+    keyed.take_key(&1).unwrap().wake();
+    assert_eq!(count(&b_count), 1);
+}
+
+/// Taking the storage out lets the wake happen after the lock guarding it is released, so a waker
+/// that locks it again doesn't deadlock.
+#[test]
+fn wake_after_unlock() {
+    let keyed = Arc::new(Mutex::new(ArrayKeyedWakers::<u8, WakerQueue<2>, 2>::new()));
+    let relock = waker_fn({
+        let keyed = keyed.clone();
+        move || assert!(keyed.try_lock().is_ok(), "woken under the lock")
+    });
+
+    keyed.lock().unwrap().pend_key(1, &relock);
+    let taken = keyed.lock().unwrap().take_key(&1);
+    taken.unwrap().wake();
+
+    keyed.lock().unwrap().pend_key(1, &relock);
+    keyed.lock().unwrap().pend_key(2, &relock);
+    let taken: Vec<_> = keyed.lock().unwrap().take_all().collect();
+    assert_eq!(taken.len(), 2);
+    taken.into_iter().for_each(|mut w| w.wake());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn btree() {
+    use wakers::KeyedWakers;
+
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let mut keyed = KeyedWakers::<&str, WakerQueue<2>>::new();
+
+    keyed.pend_key("a", &a);
+    keyed.pend_key("b", &b);
+    keyed.wake_key(&"a");
+    assert_eq!((count(&a_count), count(&b_count)), (1, 0));
+    assert!(keyed.take_key(&"a").is_none(), "a woken key's storage is dropped");
+
+    keyed.pend_key("a", &a);
+    let mut taken: Vec<_> = keyed.take_all().collect();
+    assert_eq!((count(&a_count), count(&b_count)), (1, 0));
+    taken.iter_mut().for_each(WakersMut::wake);
+    assert_eq!((count(&a_count), count(&b_count)), (2, 1));
+    assert_eq!(keyed.take_all().count(), 0);
+}
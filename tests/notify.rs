@@ -0,0 +1,110 @@
+mod common;
+
+use common::{counter, count, poll};
+use wakers::{Register, Notify, WakerQueue, SendWakers, AtomicWakerSlot, PriorityWakers, ByWaker};
+
+/// Checks the permit accounting that holds even for storage with room for a single waiter.
+fn check_permits<W: Register>(notify: &Notify<W>) {
+    let (a_count, a) = counter();
+
+    // a permit owed to a woken waiter doesn't stop the next one from being stored
+    let mut first = notify.notified();
+    assert!(poll(&mut first, &a).is_pending());
+    notify.notify_one();
+    assert_eq!(count(&a_count), 1);
+    notify.notify_one();
+    assert!(poll(&mut first, &a).is_ready());
+    assert!(poll(&mut notify.notified(), &a).is_ready(), "the second permit must not be lost");
+    assert!(poll(&mut notify.notified(), &a).is_pending());
+
+    // stored permits don't accumulate
+    notify.notify_one();
+    notify.notify_one();
+    assert!(poll(&mut notify.notified(), &a).is_ready());
+    assert!(poll(&mut notify.notified(), &a).is_pending());
+
+    // notify_waiters releases futures that were created but never polled
+    let mut unpolled = notify.notified();
+    notify.notify_waiters();
+    assert!(poll(&mut unpolled, &a).is_ready());
+    assert!(poll(&mut notify.notified(), &a).is_pending());
+}
+
+/// Checks the behaviour that needs room for more than one waiter.
+fn check_waiters<W: Register>(notify: &Notify<W>) {
+    check_permits(notify);
+
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    // a woken waiter that drops out hands its permit on
+    let mut first = notify.notified();
+    let mut second = notify.notified();
+    assert!(poll(&mut first, &a).is_pending());
+    assert!(poll(&mut second, &b).is_pending());
+    notify.notify_one();
+    assert_eq!((count(&a_count), count(&b_count)), (1, 0));
+    drop(first);
+    assert_eq!(count(&b_count), 1);
+    assert!(poll(&mut second, &b).is_ready());
+    assert!(poll(&mut notify.notified(), &b).is_pending());
+
+    // two waits in the same task keep separate registrations
+    let mut first = notify.notified();
+    let mut second = notify.notified();
+    assert!(poll(&mut first, &a).is_pending());
+    assert!(poll(&mut second, &a).is_pending());
+    drop(first);
+    notify.notify_one();
+    assert_eq!(count(&a_count), 2, "dropping one wait must not withdraw the other");
+    assert!(poll(&mut second, &a).is_ready());
+}
+
+#[test]
+fn waker_queue() {
+    check_waiters(&Notify::new(SendWakers::new(WakerQueue::<4>::new())));
+}
+
+#[test]
+fn priority() {
+    check_waiters(&Notify::new(SendWakers::new(PriorityWakers::<4>::new())));
+}
+
+#[test]
+fn atomic_slot() {
+    check_permits(&Notify::new(AtomicWakerSlot::new()));
+}
+
+#[test]
+fn by_waker() {
+    let notify = Notify::new(ByWaker::new(SendWakers::new(WakerQueue::<4>::new())));
+    check_waiters(&notify);
+
+    // two waits in the same task share one entry, so dropping one wakes the task to restore it
+    let (a_count, a) = counter();
+    let mut first = notify.notified();
+    let mut second = notify.notified();
+    assert!(poll(&mut first, &a).is_pending());
+    assert!(poll(&mut second, &a).is_pending());
+    drop(first);
+    assert_eq!(count(&a_count), 1);
+    assert!(poll(&mut second, &a).is_pending());
+    notify.notify_one();
+    assert_eq!(count(&a_count), 2);
+    assert!(poll(&mut second, &a).is_ready());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn growable() {
+    use wakers::{SyncWakers, VecWakers, PriorityHeapWakers};
+
+    check_waiters(&Notify::new(SyncWakers::new(VecWakers::new())));
+    check_waiters(&Notify::new(SyncWakers::new(PriorityHeapWakers::new())));
+}
+
+#[cfg(feature = "slab")]
+#[test]
+fn slab() {
+    check_waiters(&Notify::new(SendWakers::new(wakers::SlabWakers::new())));
+}
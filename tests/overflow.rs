@@ -0,0 +1,143 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use common::{Counter, counter, count, waker_fn, logging, woken};
+use wakers::{Wakers, WakersMut, WakerQueue, SendWakers, SyncWakers, Lock, PriorityWakers, PendOutcome};
+use wakers::overflow::{WakeEvicted, WakeAll, Reject, DebugPanic};
+
+/// A [`Lock`] that panics rather than deadlocking when it's taken twice.
+struct CheckedMutex<T>(Mutex<T>);
+
+impl<T> Lock<T> for CheckedMutex<T> {
+    fn new(value: T) -> Self {
+        Self(Mutex::new(value))
+    }
+
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        f(&mut self.0.try_lock().expect("lock taken while already held"))
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().unwrap()
+    }
+
+    fn into_inner(self) -> T {
+        self.0.into_inner().unwrap()
+    }
+}
+
+/// Fills a two-slot container, then overflows it so that `a` is evicted. `a` must only be woken once
+/// the container is free again, so that it can pend the waker counted by `b`.
+fn check_repend<W: Wakers>(wakers: &W, a: Waker, b: &Counter) {
+    let (c_count, c) = counter();
+    let (_, d) = counter();
+
+    wakers.pend_by_ref(&a);
+    wakers.pend_by_ref(&c);
+    wakers.pend_by_ref(&d);
+    assert_eq!(count(&c_count), 1, "c must make way for b");
+    assert_eq!(count(b), 0, "b must be registered, not turned away");
+
+    wakers.wake_by_ref();
+    assert_eq!(count(b), 1);
+}
+
+fn check_sync<W: Wakers + Send + Sync + 'static>(wakers: W) {
+    let wakers = Arc::new(wakers);
+    let (b_count, b) = counter();
+    let a = waker_fn({
+        let wakers = wakers.clone();
+        move || wakers.pend_by_ref(&b)
+    });
+    check_repend(&*wakers, a, &b_count);
+}
+
+fn checked<W>(wakers: W) -> SyncWakers<W, CheckedMutex<W>> {
+    SyncWakers::from_lock(Lock::new(wakers))
+}
+
+#[test]
+fn sync_wakes_evicted_outside_lock() {
+    check_sync(checked(WakerQueue::<2, WakeEvicted>::new()));
+    check_sync(checked(WakerQueue::<2, WakeAll>::new()));
+    check_sync(checked(PriorityWakers::<2, WakeAll>::new()));
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+fn cs_wakes_evicted_outside_borrow() {
+    use wakers::{CsWakers, CsLock};
+
+    check_sync(CsWakers::from_lock(CsLock::new(WakerQueue::<2, WakeEvicted>::new())));
+    check_sync(CsWakers::from_lock(CsLock::new(WakerQueue::<2, WakeAll>::new())));
+}
+
+#[test]
+fn send_wakes_evicted_outside_borrow() {
+    thread_local! {
+        static WAKERS: SendWakers<WakerQueue<2>> = const { SendWakers::new(WakerQueue::new()) };
+    }
+
+    let (b_count, b) = counter();
+    let a = waker_fn(move || WAKERS.with(|wakers| wakers.pend_by_ref(&b)));
+    WAKERS.with(|wakers| check_repend(wakers, a, &b_count));
+}
+
+#[test]
+fn wake_evicted() {
+    let (log, w) = logging(3);
+    let mut wakers = WakerQueue::<2, WakeEvicted>::new();
+
+    wakers.pend(&w[0]);
+    wakers.pend(&w[1]);
+    wakers.pend(&w[2]);
+    assert_eq!(woken(&log), [0], "only the oldest waker is pushed out");
+    wakers.wake();
+    assert_eq!(woken(&log), [0, 1, 2]);
+}
+
+#[test]
+fn wake_all() {
+    let (log, w) = logging(3);
+    let mut wakers = WakerQueue::<2, WakeAll>::new();
+
+    wakers.pend(&w[0]);
+    wakers.pend(&w[1]);
+    wakers.pend(&w[2]);
+    assert_eq!(woken(&log), [0, 1]);
+    assert_eq!(wakers.len(), 1);
+    wakers.wake();
+    assert_eq!(woken(&log), [0, 1, 2]);
+}
+
+#[test]
+fn reject() {
+    let (log, w) = logging(3);
+    let mut wakers = WakerQueue::<2, Reject>::new();
+
+    wakers.pend(&w[0]);
+    wakers.pend(&w[1]);
+    wakers.pend(&w[2]);
+    assert_eq!(woken(&log), [2], "a rejected waker is woken straight away rather than left waiting");
+    wakers.wake();
+    assert_eq!(woken(&log), [2, 0, 1]);
+
+    // a higher priority can't make room either
+    let mut wakers = PriorityWakers::<1, Reject>::new();
+    assert_eq!(wakers.pend_with_priority(&w[0], 0), PendOutcome::Inserted);
+    assert_eq!(wakers.pend_with_priority(&w[1], 1), PendOutcome::Full);
+    assert_eq!(wakers.len(), 1);
+    assert_eq!(woken(&log), [2, 0, 1]);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "waker storage overflowed")]
+fn debug_panic() {
+    let (_, w) = logging(2);
+    let mut wakers = WakerQueue::<1, DebugPanic>::new();
+
+    wakers.pend(&w[0]);
+    wakers.pend(&w[1]);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use std::task::Waker;
+use common::{logging, woken};
+use wakers::{WakersMut, PriorityWakers, PendOutcome};
+
+/// Pending with a priority, whichever storage backs it.
+trait Prioritized: WakersMut {
+    fn pend_at(&mut self, waker: &Waker, priority: u8) -> PendOutcome;
+}
+
+impl<const N: usize> Prioritized for PriorityWakers<N> {
+    fn pend_at(&mut self, waker: &Waker, priority: u8) -> PendOutcome {
+        self.pend_with_priority(waker, priority)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Prioritized for wakers::PriorityHeapWakers {
+    fn pend_at(&mut self, waker: &Waker, priority: u8) -> PendOutcome {
+        self.pend_with_priority(waker, priority)
+    }
+}
+
+fn check_order<W: Prioritized>(mut wakers: W) {
+    let (log, w) = logging(6);
+
+    for (w, priority) in w.iter().zip([1, 3, 1, 0, 3]) {
+        assert_eq!(wakers.pend_at(w, priority), PendOutcome::Inserted);
+    }
+    assert!(wakers.wake_one());
+    assert_eq!(woken(&log), [1]);
+    assert_eq!(wakers.wake_n(2), 2);
+    assert_eq!(woken(&log), [1, 4, 0]);
+
+    // plain `pend` is the lowest priority, behind everything already waiting at it
+    wakers.pend(&w[5]);
+    wakers.wake();
+    assert_eq!(woken(&log), [1, 4, 0, 2, 3, 5]);
+}
+
+fn check_repend<W: Prioritized>(mut wakers: W) {
+    let (log, w) = logging(3);
+
+    wakers.pend_at(&w[0], 1);
+    wakers.pend_at(&w[1], 1);
+    wakers.pend_at(&w[2], 1);
+    assert_eq!(wakers.pend_at(&w[0], 1), PendOutcome::AlreadyRegistered);
+    wakers.pend_at(&w[2], 2);
+    assert_eq!(wakers.len(), 3);
+
+    wakers.wake();
+    assert_eq!(woken(&log), [2, 0, 1], "pending at the same priority keeps a waiter's place");
+}
+
+#[test]
+fn bounded() {
+    check_order(PriorityWakers::<6>::new());
+    check_repend(PriorityWakers::<3>::new());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn heap() {
+    check_order(wakers::PriorityHeapWakers::new());
+    check_repend(wakers::PriorityHeapWakers::new());
+}
+
+/// Once full, the waiter that would be woken last makes way, which may be the newcomer itself.
+#[test]
+fn bounded_overflow() {
+    let (log, w) = logging(4);
+    let mut wakers = PriorityWakers::<2>::new();
+
+    wakers.pend_at(&w[0], 1);
+    wakers.pend_at(&w[1], 1);
+    assert_eq!(wakers.pend_at(&w[2], 2), PendOutcome::Evicted);
+    assert_eq!(woken(&log), [1], "the newest of the lowest priority is pushed out");
+    assert_eq!(wakers.pend_at(&w[3], 1), PendOutcome::Full);
+    assert_eq!(woken(&log), [1]);
+
+    wakers.wake();
+    assert_eq!(woken(&log), [1, 2, 0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn heap_keyed() {
+    use wakers::{RegisterMut, PriorityHeapWakers};
+
+    let (log, w) = logging(4);
+    let mut wakers = PriorityHeapWakers::new();
+
+    wakers.pend_at(&w[0], 1);
+    let key = wakers.register_key(None, &w[1]).unwrap();
+    wakers.pend_at(&w[2], 0);
+
+    // re-registering under a live key swaps the waker in place, keeping its place in line
+    for _ in 0..100 {
+        assert_eq!(wakers.register_key(Some(key), &w[1]), Some(key));
+    }
+    assert_eq!(wakers.register_key(Some(key), &w[3]), Some(key));
+    assert_eq!(wakers.len(), 3);
+
+    // churn leaves the order of everything else alone
+    for _ in 0..100 {
+        let other = wakers.register_key(None, &w[1]).unwrap();
+        assert!(wakers.deregister_key(other));
+    }
+    wakers.pend_at(&w[2], 2);
+    wakers.pend_at(&w[2], 0);
+    wakers.wake();
+    assert_eq!(woken(&log), [0, 3, 2]);
+    assert!(!wakers.deregister_key(key));
+}
+
+#[test]
+fn iterators() {
+    let (_, w) = logging(2);
+    let mut wakers = PriorityWakers::<2>::new();
+
+    wakers.pend_at(&w[0], 1);
+    wakers.pend_at(&w[1], 4);
+    assert!(wakers.iter().zip([&w[1], &w[0]]).all(|(a, b)| a.will_wake(b)));
+    assert_eq!(wakers.iter_with_priority().map(|(priority, _)| priority).collect::<Vec<_>>(), [4, 1]);
+
+    #[cfg(feature = "alloc")]
+    {
+        let mut wakers = wakers::PriorityHeapWakers::new();
+        wakers.pend_at(&w[0], 1);
+        wakers.pend_at(&w[1], 4);
+        assert_eq!(wakers.iter().count(), 2);
+        let mut priorities = wakers.iter_with_priority().map(|(priority, _)| priority).collect::<Vec<_>>();
+        priorities.sort();
+        assert_eq!(priorities, [1, 4]);
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{counter, count};
+use wakers::{Register, WakersRef, WakerQueue, SendWakers, AtomicWakerSlot, PriorityWakers, GenerationWakers, Registration};
+
+/// Checks the `Registration` lifecycle against any container with room for at least one waker.
+fn check_registration<W: Register>(wakers: &W) {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    // dropping a registration withdraws it without waking it
+    drop(wakers.register(&a));
+    wakers.wake_by_ref();
+    assert_eq!(count(&a_count), 0);
+
+    // pending again updates the same entry, even with another waker
+    let mut registration = wakers.register(&a);
+    assert!(registration.pend(&a));
+    assert!(registration.pend(&b));
+    wakers.wake_by_ref();
+    assert_eq!((count(&a_count), count(&b_count)), (0, 1));
+
+    // once woken there's nothing left to withdraw, but pending registers afresh
+    assert!(!registration.deregister());
+    assert!(registration.pend(&a));
+    wakers.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+    drop(registration);
+
+    let mut registration = wakers.register(&a);
+    assert!(registration.deregister());
+    assert!(!registration.deregister());
+    wakers.wake_by_ref();
+    assert_eq!(count(&a_count), 1);
+}
+
+#[test]
+fn containers() {
+    check_registration(&SendWakers::new(WakerQueue::<1>::new()));
+    check_registration(&SendWakers::new(PriorityWakers::<1>::new()));
+    check_registration(&GenerationWakers::new(SendWakers::new(WakerQueue::<1>::new())));
+    check_registration(&AtomicWakerSlot::new());
+    #[cfg(feature = "std")]
+    check_registration(&wakers::SyncWakers::new(wakers::VecWakers::new()));
+    #[cfg(feature = "critical-section")]
+    check_registration(&wakers::CsWakers::from_lock(wakers::CsLock::new(WakerQueue::<1>::new())));
+    #[cfg(feature = "slab")]
+    check_registration(&SendWakers::new(wakers::SlabWakers::new()));
+}
+
+/// A cancelled waiter no longer takes up room in bounded storage.
+#[test]
+fn dropped_registration_frees_room() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let wakers = SendWakers::new(WakerQueue::<1>::new());
+
+    drop(wakers.register(&a));
+    let _live = wakers.register(&b);
+    assert_eq!(count(&a_count), 0, "nothing was evicted");
+    wakers.wake_by_ref();
+    assert_eq!(count(&b_count), 1);
+}
+
+/// A waker that can't be stored is woken straight away, so its task polls again instead of hanging.
+#[test]
+fn full() {
+    use wakers::overflow::Reject;
+
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let wakers = SendWakers::new(WakerQueue::<1, Reject>::new());
+
+    let mut first = wakers.register(&a);
+    let mut second = Registration::new(&wakers);
+    assert!(!second.pend(&b));
+    assert_eq!((count(&a_count), count(&b_count)), (0, 1));
+    assert!(!second.deregister());
+    assert!(first.deregister());
+}
@@ -0,0 +1,277 @@
+mod common;
+
+use common::{counter, count};
+use wakers::{WakersMut, WakersRef, Wakers, WakerQueue, SendWakers, AtomicWakerSlot, GenerationWakers, PendOutcome};
+
+/// Checks a shared container against the `WakersRef` contract.
+fn check_by_ref<W: Wakers>(wakers: &W) {
+    let (counter, waker) = counter();
+
+    wakers.pend_by_ref(&waker);
+    wakers.wake_and_retain_by_ref();
+    assert_eq!(count(&counter), 1);
+    wakers.wake_and_retain_by_ref();
+    assert_eq!(count(&counter), 2, "wake_and_retain_by_ref must keep the waker registered");
+
+    wakers.wake_by_ref();
+    assert_eq!(count(&counter), 3);
+    wakers.wake_by_ref();
+    assert_eq!(count(&counter), 3, "wake_by_ref must clear the waker");
+
+    wakers.pend_by_ref(&waker);
+    wakers.wake_and_clear_by_ref();
+    wakers.wake_and_clear_by_ref();
+    assert_eq!(count(&counter), 4, "wake_and_clear_by_ref must clear the waker");
+}
+
+/// Checks an exclusively owned container against the `WakersMut` contract.
+fn check_mut<W: WakersMut>(mut wakers: W) {
+    let (counter, waker) = counter();
+
+    wakers.pend(&waker);
+    wakers.wake_and_retain();
+    wakers.wake_and_retain();
+    assert_eq!(count(&counter), 2, "wake_and_retain must keep the waker registered");
+
+    wakers.wake();
+    wakers.wake();
+    assert_eq!(count(&counter), 3, "wake must clear the waker");
+
+    wakers.pend(&waker);
+    wakers.wake_and_clear();
+    wakers.wake_and_clear();
+    assert_eq!(count(&counter), 4, "wake_and_clear must clear the waker");
+}
+
+/// Checks the outcome reported for each way of pending into a container with room for two wakers,
+/// that evicts the oldest once it's full.
+fn check_outcomes<W: Wakers>(wakers: &W) {
+    let (a_count, a) = counter();
+    let (_, b) = counter();
+    let (_, c) = counter();
+
+    assert_eq!(wakers.try_pend_by_ref(&a), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend_by_ref(&a), PendOutcome::AlreadyRegistered);
+    assert_eq!(wakers.try_pend_by_ref(&b), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend_by_ref(&c), PendOutcome::Evicted);
+    assert_eq!(count(&a_count), 1, "the evicted waker is woken");
+}
+
+#[test]
+fn pend_outcomes() {
+    use wakers::overflow::Reject;
+
+    check_outcomes(&SendWakers::new(WakerQueue::<2>::new()));
+    #[cfg(feature = "std")]
+    check_outcomes(&wakers::SyncWakers::new(WakerQueue::<2>::new()));
+    #[cfg(feature = "critical-section")]
+    check_outcomes(&wakers::CsWakers::from_lock(wakers::CsLock::new(WakerQueue::<2>::new())));
+
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let mut wakers = WakerQueue::<1, Reject>::new();
+    assert_eq!(wakers.try_pend(&a), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend(&b), PendOutcome::Full);
+    assert_eq!(count(&b_count), 0, "try_pend leaves a rejected waker to the caller");
+    assert_eq!(wakers.len(), 1);
+    wakers.wake();
+    assert_eq!(count(&a_count), 1);
+
+    assert!(PendOutcome::Inserted.is_registered());
+    assert!(PendOutcome::AlreadyRegistered.is_registered());
+    assert!(PendOutcome::Evicted.is_registered());
+    assert!(!PendOutcome::Full.is_registered());
+}
+
+#[test]
+fn waker_queue() {
+    check_mut(WakerQueue::<4>::new());
+}
+
+/// `WakerQueue` used to implement `WakersRef` with `wake_by_ref` leaving its wakers registered.
+#[test]
+#[allow(deprecated)]
+fn waker_queue_retaining_shim() {
+    let (counter, waker) = counter();
+    let mut wakers = WakerQueue::<4>::new();
+
+    wakers.pend(&waker);
+    wakers.wake_by_ref();
+    wakers.wake_by_ref();
+    assert_eq!(count(&counter), 2);
+    assert_eq!(wakers.len(), 1);
+}
+
+#[test]
+fn send_wakers() {
+    check_mut(SendWakers::new(WakerQueue::<4>::new()));
+    check_by_ref(&SendWakers::new(WakerQueue::<4>::new()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sync_wakers() {
+    use wakers::SyncWakers;
+
+    check_mut(SyncWakers::new(WakerQueue::<4>::new()));
+    check_by_ref(&SyncWakers::new(WakerQueue::<4>::new()));
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+fn cs_wakers() {
+    use wakers::{CsWakers, CsLock};
+
+    check_mut(CsWakers::from_lock(CsLock::new(WakerQueue::<4>::new())));
+    check_by_ref(&CsWakers::from_lock(CsLock::new(WakerQueue::<4>::new())));
+}
+
+#[test]
+fn atomic_waker_slot() {
+    check_by_ref(&AtomicWakerSlot::new());
+}
+
+#[test]
+fn generation_wakers() {
+    check_mut(GenerationWakers::new(WakerQueue::<4>::new()));
+    check_by_ref(&GenerationWakers::new(SendWakers::new(WakerQueue::<4>::new())));
+
+    let wakers = GenerationWakers::new(SendWakers::new(WakerQueue::<4>::new()));
+    let epoch = wakers.epoch();
+    wakers.wake_and_retain_by_ref();
+    assert_ne!(wakers.epoch(), epoch, "a retaining wake is still a wake");
+}
+
+#[test]
+fn introspection() {
+    let (_, a) = counter();
+    let (_, b) = counter();
+
+    let wakers = SendWakers::new(WakerQueue::<4>::new());
+    assert!(wakers.is_empty());
+    assert_eq!(wakers.capacity(), Some(4));
+    wakers.pend_by_ref(&a);
+    wakers.pend_by_ref(&b);
+    wakers.pend_by_ref(&a);
+    assert_eq!(wakers.len(), 2);
+    assert!(wakers.inspect(|w| w.iter().zip([&a, &b]).all(|(w, expected)| w.will_wake(expected))));
+
+    wakers.wake_one_by_ref();
+    assert_eq!(wakers.len(), 1);
+    wakers.wake_and_retain_by_ref();
+    assert_eq!(wakers.len(), 1);
+    wakers.wake_by_ref();
+    assert!(wakers.is_empty());
+}
+
+/// Checks that `remove_by_ref` drops exactly the matching waker, without waking anything.
+fn check_remove<W: Wakers>(wakers: &W) {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    wakers.pend_by_ref(&a);
+    wakers.pend_by_ref(&b);
+    assert!(wakers.remove_by_ref(&a));
+    assert!(!wakers.remove_by_ref(&a));
+    assert_eq!(count(&a_count), 0, "remove_by_ref must not wake");
+
+    wakers.wake_by_ref();
+    assert_eq!((count(&a_count), count(&b_count)), (0, 1));
+}
+
+#[test]
+fn remove() {
+    check_remove(&SendWakers::new(WakerQueue::<4>::new()));
+    check_remove(&GenerationWakers::new(SendWakers::new(WakerQueue::<4>::new())));
+
+    let (counter, waker) = counter();
+    let slot = AtomicWakerSlot::new();
+    slot.pend_by_ref(&waker);
+    assert!(slot.remove_by_ref(&waker));
+    slot.wake_by_ref();
+    assert_eq!(count(&counter), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn remove_std() {
+    use wakers::{SyncWakers, VecWakers, PriorityWakers, PriorityHeapWakers};
+
+    check_remove(&SyncWakers::new(VecWakers::new()));
+    check_remove(&SyncWakers::new(PriorityWakers::<4>::new()));
+    check_remove(&SyncWakers::new(PriorityHeapWakers::new()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn waiter_list() {
+    use wakers::WaiterList;
+
+    check_mut(WaiterList::new());
+    check_by_ref(&WaiterList::new());
+    check_remove(&WaiterList::new());
+}
+
+#[cfg(feature = "slab")]
+#[test]
+fn remove_slab() {
+    check_remove(&SendWakers::new(wakers::SlabWakers::new()));
+}
+
+/// Checks identity-indexed dedup stays consistent through every way of adding and removing wakers.
+#[cfg(feature = "alloc")]
+fn check_dedup<W: wakers::RegisterMut<Key = K>, K: Copy>(mut wakers: W) {
+    let wakers_list: Vec<_> = (0..1000).map(|_| counter()).collect();
+    for (_, w) in &wakers_list {
+        assert_eq!(wakers.try_pend(w), PendOutcome::Inserted);
+    }
+    for (_, w) in &wakers_list {
+        assert_eq!(wakers.try_pend(w), PendOutcome::AlreadyRegistered);
+    }
+    assert_eq!(wakers.len(), 1000);
+
+    assert_eq!(wakers.wake_n(500), 500);
+    assert_eq!(wakers.try_pend(&wakers_list[0].1), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend(&wakers_list[999].1), PendOutcome::AlreadyRegistered);
+    assert!(wakers.remove(&wakers_list[999].1));
+    assert_eq!(wakers.try_pend(&wakers_list[999].1), PendOutcome::Inserted);
+
+    // a keyed registration of an already stored waker leaves the original in place when withdrawn
+    let key = wakers.register_key(None, &wakers_list[0].1).unwrap();
+    assert!(wakers.deregister_key(key));
+    assert_eq!(wakers.try_pend(&wakers_list[0].1), PendOutcome::AlreadyRegistered);
+
+    wakers.wake();
+    assert_eq!(wakers.try_pend(&wakers_list[0].1), PendOutcome::Inserted);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn dedup() {
+    check_dedup(wakers::VecWakers::new());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn dedup_heap() {
+    use wakers::PriorityHeapWakers;
+
+    let mut wakers = PriorityHeapWakers::new();
+    let wakers_list: Vec<_> = (0..1000).map(|_| counter()).collect();
+    for (_, w) in &wakers_list {
+        assert_eq!(wakers.try_pend(w), PendOutcome::Inserted);
+    }
+    assert_eq!(wakers.pend_with_priority(&wakers_list[0].1, 1), PendOutcome::AlreadyRegistered);
+    assert_eq!(wakers.wake_n(1), 1);
+    assert_eq!(count(&wakers_list[0].0), 1);
+    assert_eq!(wakers.try_pend(&wakers_list[0].1), PendOutcome::Inserted);
+    assert!(wakers.remove(&wakers_list[1].1));
+    assert_eq!(wakers.try_pend(&wakers_list[1].1), PendOutcome::Inserted);
+    assert_eq!(wakers.len(), 1000);
+}
+
+#[cfg(feature = "slab")]
+#[test]
+fn dedup_slab() {
+    check_dedup(wakers::SlabWakers::new());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+use common::{counter, count};
+use wakers::{Wakers, WakersRef, WakersMut, WakerQueue, SendWakers, Registration};
+
+type Storage = SendWakers<WakerQueue<2>>;
+type Callback = fn(&Storage);
+
+thread_local! {
+    static WAKERS: Storage = const { SendWakers::new(WakerQueue::new()) };
+}
+
+/// A waker that calls back into [`WAKERS`] once its last clone is dropped.
+struct OnDrop(Callback);
+
+impl Wake for OnDrop {
+    fn wake(self: Arc<Self>) { }
+}
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        WAKERS.with(self.0)
+    }
+}
+
+fn on_drop(f: Callback) -> Waker {
+    Arc::new(OnDrop(f)).into()
+}
+
+/// Replacing a registered waker drops the old one while the storage is borrowed; a wake it makes from
+/// its destructor must still be delivered as soon as the replacement returns.
+#[test]
+fn registration_replacing_waker_that_wakes_on_drop() {
+    let (counter, waker) = counter();
+
+    WAKERS.with(|wakers| {
+        let mut registration = Registration::new(wakers);
+        registration.pend(&on_drop(|w| w.wake_by_ref()));
+        registration.pend(&waker);
+        assert_eq!(count(&counter), 1, "the deferred wake must not wait for another pend");
+        assert!(wakers.is_empty());
+    });
+}
+
+/// Each kind of wake deferred from within a borrow is delivered when that borrow ends.
+#[test]
+fn deferred_wakes_flush_when_borrow_ends() {
+    let checks: [(Callback, bool); 3] = [
+        (|w| w.wake_by_ref(), false),
+        (|w| { w.wake_one_by_ref(); }, false),
+        (|w| w.wake_and_retain_by_ref(), true),
+    ];
+
+    for (wake, retained) in checks {
+        let (counter, waker) = counter();
+
+        WAKERS.with(|wakers| {
+            wakers.pend_by_ref(&waker);
+            let mut registration = Registration::new(wakers);
+            registration.pend(&on_drop(wake));
+            assert!(registration.deregister());
+            assert_eq!(count(&counter), 1, "the deferred wake must be delivered by deregister");
+            assert_eq!(wakers.len(), retained as usize);
+        });
+    }
+}
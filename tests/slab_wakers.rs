@@ -0,0 +1,118 @@
+#![cfg(feature = "slab")]
+
+mod common;
+
+use common::{counter, count};
+use wakers::{WakersMut, RegisterMut, SlabWakers};
+
+#[test]
+fn keys_address_their_entry() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    let mut wakers = SlabWakers::new();
+    let key = wakers.register(&a);
+    assert!(wakers.update(key, &b));
+    wakers.wake();
+    assert_eq!((count(&a_count), count(&b_count)), (0, 1));
+
+    let key = wakers.register(&a);
+    assert!(wakers.deregister(key).unwrap().will_wake(&a));
+    assert!(wakers.deregister(key).is_none());
+    assert!(wakers.is_empty());
+}
+
+#[test]
+fn stale_keys_miss_reused_slots() {
+    let (a_count, a) = counter();
+    let (_, b) = counter();
+
+    let mut wakers = SlabWakers::new();
+    let stale = wakers.register(&a);
+    wakers.wake();
+
+    // the slab hands the freed slot straight to the next entry
+    let key = wakers.register(&a);
+    assert_ne!(key, stale);
+    assert!(!wakers.update(stale, &b), "a stale key must not replace another task's waker");
+    assert!(wakers.deregister(stale).is_none(), "a stale key must not remove another task's waker");
+    assert!(!wakers.deregister_key(stale));
+
+    wakers.wake();
+    assert_eq!(count(&a_count), 2);
+}
+
+#[test]
+fn unbounded() {
+    let wakers_list: Vec<_> = (0..100).map(|_| counter()).collect();
+
+    let mut wakers = SlabWakers::new();
+    for (_, w) in &wakers_list {
+        wakers.pend(w);
+    }
+    assert_eq!(wakers.len(), 100);
+    assert_eq!(wakers.capacity(), None);
+
+    wakers.wake();
+    assert!(wakers_list.iter().all(|(c, _)| count(c) == 1));
+}
+
+#[test]
+fn take_n_wakes_longest_waiting() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let (c_count, c) = counter();
+    let (d_count, d) = counter();
+
+    let mut wakers = SlabWakers::new();
+    let key_a = wakers.register(&a);
+    let key_b = wakers.register(&b);
+    wakers.register(&c);
+    assert!(wakers.deregister(key_a).is_some());
+    // d reuses a's slot, but still waits behind b and c
+    wakers.register(&d);
+    assert!(wakers.update(key_b, &b));
+
+    assert_eq!(wakers.wake_n(1), 1);
+    assert_eq!(count(&b_count), 1);
+    assert_eq!(wakers.wake_n(1), 1);
+    assert_eq!(count(&c_count), 1);
+    assert_eq!((count(&a_count), count(&d_count)), (0, 0));
+    assert_eq!(wakers.wake_n(2), 1);
+    assert_eq!(count(&d_count), 1);
+    assert_eq!(wakers.wake_n(1), 0);
+}
+
+#[test]
+fn churn_without_waking() {
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    let mut wakers = SlabWakers::new();
+    wakers.register(&a);
+    for _ in 0..1000 {
+        let key = wakers.register(&b);
+        assert!(wakers.deregister_key(key));
+    }
+    wakers.register(&b);
+
+    assert_eq!(wakers.wake_n(1), 1);
+    assert_eq!((count(&a_count), count(&b_count)), (1, 0));
+    assert_eq!(wakers.wake_n(1), 1);
+    assert_eq!(count(&b_count), 1);
+}
+
+#[test]
+fn iter_keyed() {
+    let (_, a) = counter();
+    let (_, b) = counter();
+
+    let mut wakers = SlabWakers::new();
+    let a_key = wakers.register(&a);
+    let b_key = wakers.register(&b);
+    assert!(wakers.deregister(a_key).is_some());
+
+    assert_eq!(wakers.iter().count(), 1);
+    let (key, waker) = wakers.iter_keyed().next().unwrap();
+    assert!(key == b_key && waker.will_wake(&b));
+}
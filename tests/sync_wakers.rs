@@ -0,0 +1,136 @@
+mod common;
+
+#[cfg(feature = "lock_api")]
+mod lock_api_mutex {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use lock_api::{RawMutex, GuardSend};
+    use super::common::{counter, count, waker_fn};
+    use wakers::{Wakers, WakersRef, SyncWakers, WakerQueue};
+
+    /// The simplest `no_std`-style lock there is.
+    struct RawSpin(AtomicBool);
+
+    unsafe impl RawMutex for RawSpin {
+        const INIT: Self = RawSpin(AtomicBool::new(false));
+        type GuardMarker = GuardSend;
+
+        fn lock(&self) {
+            while !self.try_lock() {
+                std::hint::spin_loop();
+            }
+        }
+
+        fn try_lock(&self) -> bool {
+            self.0.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+        }
+
+        unsafe fn unlock(&self) {
+            self.0.store(false, Ordering::Release)
+        }
+    }
+
+    type SpinWakers<W> = SyncWakers<W, lock_api::Mutex<RawSpin, W>>;
+
+    static WAKERS: SpinWakers<WakerQueue<4>> =
+        SyncWakers::from_lock(lock_api::Mutex::const_new(RawSpin::INIT, WakerQueue::new()));
+
+    #[test]
+    fn static_between_threads() {
+        let counters: Vec<_> = (0..4).map(|_| counter()).collect();
+        let threads: Vec<_> = counters.iter().map(|(_, waker)| thread::spawn({
+            let waker = waker.clone();
+            move || for _ in 0..100 {
+                WAKERS.pend_by_ref(&waker);
+                WAKERS.wake_by_ref();
+            }
+        })).collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert!(WAKERS.inspect(|w| w.iter().next().is_none()));
+        for (c, _) in &counters {
+            assert!((1..=100).contains(&count(c)));
+        }
+    }
+
+    /// A spinlock can't be re-entered, so this only finishes if wakers are woken outside of it.
+    #[test]
+    fn repend_while_woken() {
+        let wakers: Arc<SpinWakers<WakerQueue<2>>> = Arc::new(SyncWakers::from_lock(lock_api::Mutex::new(WakerQueue::new())));
+
+        let (a_count, a) = counter();
+        let repend = waker_fn({
+            let wakers = wakers.clone();
+            move || {
+                wakers.pend_by_ref(&a);
+            }
+        });
+
+        wakers.pend_by_ref(&repend);
+        wakers.wake_by_ref();
+        wakers.wake_by_ref();
+        assert_eq!(count(&a_count), 1);
+    }
+}
+
+#[cfg(feature = "std")]
+mod poison {
+    use std::panic::{self, AssertUnwindSafe};
+    use super::common::{counter, count};
+    use wakers::{Wakers, WakersRef, WakersMut, SyncWakers, WakerQueue};
+
+    /// Panics while holding the lock, poisoning it.
+    fn poison<W>(wakers: &SyncWakers<W>) {
+        let panicked = panic::catch_unwind(AssertUnwindSafe(|| wakers.inspect(|_| panic!("poisoned"))));
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    fn recovers() {
+        let (a_count, a) = counter();
+        let wakers = SyncWakers::new(WakerQueue::<2>::new());
+
+        wakers.pend_by_ref(&a);
+        poison(&wakers);
+        wakers.wake_by_ref();
+        assert_eq!(count(&a_count), 1);
+
+        poison(&wakers);
+        wakers.pend_by_ref(&a);
+        assert_eq!(wakers.len(), 1);
+        poison(&wakers);
+        assert!(wakers.remove_by_ref(&a));
+    }
+
+    #[test]
+    fn debug_and_clone_never_panic() {
+        let (a_count, a) = counter();
+        let wakers = SyncWakers::new(WakerQueue::<2>::new());
+
+        wakers.pend_by_ref(&a);
+        poison(&wakers);
+        assert!(format!("{:?}", wakers).contains("WakerQueue"));
+
+        poison(&wakers);
+        let mut clone = wakers.clone();
+        assert_eq!(clone.len(), 1);
+        clone.wake();
+        assert_eq!(count(&a_count), 1);
+    }
+
+    #[test]
+    fn owned_access() {
+        let (a_count, a) = counter();
+        let mut wakers = SyncWakers::new(WakerQueue::<2>::new());
+
+        wakers.pend_by_ref(&a);
+        poison(&wakers);
+        assert_eq!(wakers.get_mut().len(), 1);
+        poison(&wakers);
+        wakers.into_inner().wake();
+        assert_eq!(count(&a_count), 1);
+    }
+}
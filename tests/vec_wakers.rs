@@ -0,0 +1,114 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{counter, count, logging, woken};
+use wakers::{WakersMut, VecWakers, PendOutcome};
+
+#[test]
+fn grows_without_evicting() {
+    let counters: Vec<_> = (0..100).map(|_| counter()).collect();
+    let mut wakers = VecWakers::new();
+    assert_eq!(wakers.capacity(), None);
+
+    for (_, w) in &counters {
+        assert_eq!(wakers.try_pend(w), PendOutcome::Inserted);
+    }
+    assert_eq!(wakers.len(), 100);
+    assert!(counters.iter().all(|(c, _)| count(c) == 0), "nothing is woken to make room");
+
+    wakers.wake();
+    assert!(counters.iter().all(|(c, _)| count(c) == 1));
+    assert!(wakers.is_empty());
+}
+
+#[test]
+fn fifo() {
+    let (log, w) = logging(4);
+    let mut wakers = VecWakers::with_capacity(2);
+
+    for w in &w {
+        wakers.pend(w);
+    }
+    assert_eq!(wakers.wake_n(2), 2);
+    assert_eq!(woken(&log), [0, 1]);
+
+    wakers.pend(&w[0]);
+    wakers.wake();
+    assert_eq!(woken(&log), [0, 1, 2, 3, 0]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sync_wakers() {
+    use wakers::{Wakers, WakersRef, SyncWakers};
+
+    let (log, w) = logging(3);
+    let wakers = SyncWakers::new(VecWakers::new());
+    for w in &w {
+        wakers.pend_by_ref(w);
+    }
+    wakers.wake_one_by_ref();
+    wakers.wake_by_ref();
+    assert_eq!(woken(&log), [0, 1, 2]);
+}
+
+/// Semaphore-style use: waking one at a time while others keep joining the back of the queue.
+#[test]
+fn wake_one_interleaved() {
+    let (log, w) = logging(8);
+    let mut wakers = VecWakers::new();
+
+    wakers.pend(&w[0]);
+    wakers.pend(&w[1]);
+    for (i, w) in w.iter().enumerate().skip(2) {
+        wakers.pend(w);
+        assert!(wakers.wake_one());
+        assert_eq!(woken(&log).last(), Some(&(i - 2)));
+    }
+    assert_eq!(wakers.len(), 2);
+    wakers.wake();
+    assert_eq!(woken(&log), (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn keyed() {
+    use wakers::RegisterMut;
+
+    let (log, w) = logging(4);
+    let mut wakers = VecWakers::new();
+
+    let keys: Vec<_> = w.iter().map(|w| wakers.register_key(None, w).unwrap()).collect();
+    assert_eq!(wakers.register_key(Some(keys[1]), &w[1]), Some(keys[1]), "a live key keeps its entry");
+    assert!(wakers.deregister_key(keys[1]));
+    assert!(!wakers.deregister_key(keys[1]));
+    assert!(wakers.deregister_key(keys[0]));
+    assert_eq!(wakers.len(), 2);
+
+    // a withdrawn key registers afresh, at the back of the queue
+    let key = wakers.register_key(Some(keys[1]), &w[1]).unwrap();
+    assert_ne!(key, keys[1]);
+    assert_eq!(wakers.wake_n(2), 2);
+    assert_eq!(woken(&log), [2, 3]);
+    assert!(!wakers.deregister_key(keys[2]), "a woken key is gone");
+    wakers.wake();
+    assert_eq!(woken(&log), [2, 3, 1]);
+}
+
+#[test]
+fn keyed_churn() {
+    use wakers::RegisterMut;
+
+    let (log, w) = logging(2);
+    let mut wakers = VecWakers::new();
+
+    let first = wakers.register_key(None, &w[0]).unwrap();
+    for _ in 0..1000 {
+        let key = wakers.register_key(None, &w[1]).unwrap();
+        assert!(wakers.deregister_key(key));
+    }
+    assert_eq!(wakers.len(), 1);
+    assert_eq!(wakers.register_key(Some(first), &w[0]), Some(first));
+    wakers.wake();
+    assert_eq!(woken(&log), [0]);
+}
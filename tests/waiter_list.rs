@@ -0,0 +1,178 @@
+mod common;
+
+use std::pin::pin;
+use common::{counter, count};
+use wakers::{WakersRef, WaiterList, Waiter};
+
+#[test]
+fn fifo() {
+    let list = WaiterList::new();
+    let counters: Vec<_> = (0..3).map(|_| counter()).collect();
+    let mut a = pin!(Waiter::new(&list));
+    let mut b = pin!(Waiter::new(&list));
+    let mut c = pin!(Waiter::new(&list));
+    a.as_mut().pend(&counters[0].1);
+    b.as_mut().pend(&counters[1].1);
+    c.as_mut().pend(&counters[2].1);
+    // pending again keeps a waiter's place in line
+    a.as_mut().pend(&counters[0].1);
+    assert_eq!(list.len(), 3);
+
+    assert!(list.wake_one_by_ref());
+    assert_eq!(counters.iter().map(|(c, _)| count(c)).collect::<Vec<_>>(), [1, 0, 0]);
+    assert!(!a.is_pending());
+    assert_eq!(list.wake_n_by_ref(5), 2);
+    assert_eq!(counters.iter().map(|(c, _)| count(c)).collect::<Vec<_>>(), [1, 1, 1]);
+    assert!(list.is_empty());
+    assert!(!list.wake_one_by_ref());
+}
+
+#[test]
+fn drop_unlinks() {
+    let list = WaiterList::new();
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+    let (c_count, c) = counter();
+
+    let mut wa = pin!(Waiter::new(&list));
+    wa.as_mut().pend(&a);
+    {
+        let mut wb = pin!(Waiter::new(&list));
+        wb.as_mut().pend(&b);
+        let mut wc = pin!(Waiter::new(&list));
+        wc.as_mut().pend(&c);
+        assert!(wc.as_mut().deregister());
+        assert!(!wc.as_mut().deregister());
+    }
+    assert_eq!(list.len(), 1);
+
+    list.wake_by_ref();
+    assert_eq!((count(&a_count), count(&b_count), count(&c_count)), (1, 0, 0));
+    assert!(list.is_empty());
+}
+
+#[test]
+fn wake_and_retain_terminates() {
+    let list = WaiterList::new();
+    let (a_count, a) = counter();
+    let (b_count, b) = counter();
+
+    let mut wa = pin!(Waiter::new(&list));
+    let mut wb = pin!(Waiter::new(&list));
+    wa.as_mut().pend(&a);
+    wb.as_mut().pend(&b);
+
+    list.wake_and_retain_by_ref();
+    assert_eq!((count(&a_count), count(&b_count)), (1, 1));
+    assert!(wa.is_pending() && wb.is_pending());
+
+    // retained waiters keep their order
+    list.wake_one_by_ref();
+    assert_eq!((count(&a_count), count(&b_count)), (2, 1));
+}
+
+/// Bare wakers get nodes of their own, which are kept apart from the waiters'.
+#[cfg(feature = "alloc")]
+#[test]
+fn bare_wakers() {
+    use common::{logging, woken};
+    use wakers::{Wakers, WakersMut, PendOutcome};
+
+    let (log, w) = logging(3);
+    let list = WaiterList::new();
+    let mut waiter = pin!(Waiter::new(&list));
+    waiter.as_mut().pend(&w[0]);
+
+    assert_eq!(list.try_pend_by_ref(&w[1]), PendOutcome::Inserted);
+    assert_eq!(list.try_pend_by_ref(&w[1]), PendOutcome::AlreadyRegistered);
+    assert_eq!(list.try_pend_by_ref(&w[0]), PendOutcome::Inserted, "a waiter's node isn't a duplicate");
+    list.pend_by_ref(&w[2]);
+    assert_eq!(list.len(), 4);
+
+    assert!(list.remove_by_ref(&w[2]));
+    assert!(!list.remove_by_ref(&w[2]));
+    assert!(list.remove_by_ref(&w[0]));
+    assert!(waiter.is_pending(), "removing a bare waker leaves the waiter alone");
+    assert!(!list.remove_by_ref(&w[0]));
+
+    list.wake_and_retain_by_ref();
+    assert_eq!(woken(&log), [0, 1]);
+    list.wake_by_ref();
+    assert_eq!(woken(&log), [0, 1, 0, 1]);
+    assert!(list.is_empty());
+
+    // whatever is still waiting when the list is dropped is freed along with it
+    let mut list = WaiterList::new();
+    list.pend(&w[1]);
+    list.pend(&w[2]);
+    assert!(list.remove(&w[1]));
+    assert_eq!(WakersMut::len(&list), 1);
+}
+
+#[cfg(feature = "critical-section")]
+mod guarded {
+    use std::pin::Pin;
+    use std::sync::Mutex;
+    use std::thread;
+    use super::common::{counter, count, waker_fn};
+    use wakers::{WakersRef, WaiterList, Waiter, CsGuard};
+
+    fn assert_send<T: Send>(_: &T) { }
+
+    static LIST: WaiterList<CsGuard> = WaiterList::guarded();
+    static REJOINING: Mutex<Option<Pin<Box<Waiter<'static, CsGuard>>>>> = Mutex::new(None);
+
+    /// A waiter that rejoins from within its own wake is left for the next wake.
+    #[test]
+    fn rejoin_during_wake() {
+        let (rejoined_count, rejoined) = counter();
+        let (other_count, other) = counter();
+
+        let rejoining = waker_fn(move || {
+            REJOINING.lock().unwrap().as_mut().unwrap().as_mut().pend(&rejoined)
+        });
+        let mut waiter = Box::pin(Waiter::new(&LIST));
+        waiter.as_mut().pend(&rejoining);
+        *REJOINING.lock().unwrap() = Some(waiter);
+        let mut waiter = Box::pin(Waiter::new(&LIST));
+        waiter.as_mut().pend(&other);
+        assert_send(&waiter);
+
+        assert_eq!(LIST.wake_n_by_ref(usize::MAX), 2);
+        assert_eq!((count(&rejoined_count), count(&other_count)), (0, 1));
+        assert_eq!(LIST.len(), 1);
+
+        LIST.wake_by_ref();
+        assert_eq!(count(&rejoined_count), 1);
+        assert!(LIST.is_empty());
+        *REJOINING.lock().unwrap() = None;
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let list = WaiterList::<CsGuard>::guarded();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let (counter, waker) = counter();
+                    let mut waiter = Box::pin(Waiter::new(&list));
+                    waiter.as_mut().pend(&waker);
+                    // a waiter leaves the list before its waker is called, outside the guard
+                    while count(&counter) == 0 {
+                        thread::yield_now();
+                    }
+                    assert!(!waiter.is_pending());
+                    assert_eq!(count(&counter), 1);
+                });
+            }
+            s.spawn(|| {
+                let mut woken = 0;
+                while woken < 4 {
+                    woken += list.wake_n_by_ref(usize::MAX);
+                    thread::yield_now();
+                }
+            });
+        });
+        assert!(list.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::sync::Mutex;
+use common::{counter, count, logging, woken};
+use wakers::{WakersMut, WakerQueue, PendOutcome};
+
+#[test]
+fn fifo() {
+    let (log, w) = logging(3);
+    let mut wakers = WakerQueue::<3>::new();
+
+    for w in &w {
+        wakers.pend(w);
+    }
+    assert_eq!(wakers.len(), 3);
+
+    wakers.wake_one();
+    assert_eq!(woken(&log), [0]);
+    wakers.pend(&w[0]);
+    wakers.wake();
+    assert_eq!(woken(&log), [0, 1, 2, 0]);
+    assert!(wakers.is_empty());
+}
+
+#[test]
+fn dedup() {
+    let (a_count, a) = counter();
+    let (_, b) = counter();
+    let mut wakers = WakerQueue::<2>::new();
+
+    assert_eq!(wakers.try_pend(&a), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend(&a.clone()), PendOutcome::AlreadyRegistered);
+    assert_eq!(wakers.try_pend(&b), PendOutcome::Inserted);
+    assert_eq!(wakers.try_pend(&a), PendOutcome::AlreadyRegistered, "a full queue must still dedup");
+    assert_eq!(wakers.len(), 2);
+
+    wakers.wake();
+    assert_eq!(count(&a_count), 1);
+}
+
+#[test]
+fn const_new() {
+    static WAKERS: Mutex<WakerQueue<2>> = Mutex::new(WakerQueue::new());
+    let (a_count, a) = counter();
+
+    assert_eq!(WAKERS.lock().unwrap().capacity(), Some(2));
+    WAKERS.lock().unwrap().pend(&a);
+    WAKERS.lock().unwrap().wake();
+    assert_eq!(count(&a_count), 1);
+}
+
+#[cfg(feature = "const-default")]
+#[test]
+fn const_default() {
+    use const_default::ConstDefault;
+
+    const WAKERS: WakerQueue<4> = WakerQueue::DEFAULT;
+    assert!(WAKERS.is_empty());
+}